@@ -0,0 +1,155 @@
+// Ways of telling discs apart without relying on colour
+
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+
+const COLOR_PATTERN: [f32; 4] = [0.0, 0.0, 0.0, 0.45];
+const COLOR_NUMBER: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const COLOR_NUMBER_SHADOW: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+// High-contrast mode draws plain shapes instead of textures
+pub const COLOR_HC_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+pub const COLOR_HC_ROD: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+pub const COLOR_HC_HIGHLIGHT: [f32; 4] = [1.0, 0.9, 0.0, 1.0];
+pub const COLOR_HC_OUTLINE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+pub const COLOR_HC_INVALID: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DiscLabels {
+    None,
+    Numbers,
+    Patterns,
+}
+
+impl DiscLabels {
+    pub fn parse(value: &str) -> Option<DiscLabels> {
+        match value {
+            "none" => Some(DiscLabels::None),
+            "numbers" => Some(DiscLabels::Numbers),
+            "patterns" => Some(DiscLabels::Patterns),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> DiscLabels {
+        match self {
+            DiscLabels::None => DiscLabels::Numbers,
+            DiscLabels::Numbers => DiscLabels::Patterns,
+            DiscLabels::Patterns => DiscLabels::None,
+        }
+    }
+}
+
+// Number of different patterns before they repeat
+const N_PATTERNS: u32 = 6;
+// Texture rows the patterns are drawn on
+const PATTERN_ROWS: f64 = 12.0;
+
+// Draws the size number or pattern of disc `disc` (smallest is 0) over `rect`,
+// a disc drawn with `pixel_size` screen pixels per texture pixel
+pub fn render_label(
+    labels: DiscLabels,
+    disc: u32,
+    rect: [f64; 4],
+    pixel_size: f64,
+    c: graphics::Context,
+    gl: &mut opengl_graphics::GlGraphics,
+) {
+    match labels {
+        DiscLabels::None => {}
+        DiscLabels::Numbers => render_number(disc + 1, rect, c, gl),
+        DiscLabels::Patterns => render_pattern(disc % N_PATTERNS, rect, pixel_size, c, gl),
+    }
+}
+
+fn render_number(
+    number: u32,
+    [x, y, width, height]: [f64; 4],
+    c: graphics::Context,
+    gl: &mut opengl_graphics::GlGraphics,
+) {
+    let text = number.to_string();
+    let scale = (height * 0.5 / GLYPH_HEIGHT as f64).floor().max(1.0);
+    let text_x = x + (width - text_width(&text, scale) + scale) / 2.0;
+    let text_y = y + (height - GLYPH_HEIGHT as f64 * scale) / 2.0;
+
+    // A shadow keeps the number readable on light and dark discs
+    draw_text(
+        &text,
+        text_x + scale / 2.0,
+        text_y + scale / 2.0,
+        scale,
+        COLOR_NUMBER_SHADOW,
+        c,
+        gl,
+    );
+    draw_text(&text, text_x, text_y, scale, COLOR_NUMBER, c, gl);
+}
+
+fn render_pattern(
+    pattern: u32,
+    [x, y, width, height]: [f64; 4],
+    pixel_size: f64,
+    c: graphics::Context,
+    gl: &mut opengl_graphics::GlGraphics,
+) {
+    // Patterns are laid out on the pixel grid of the disc texture, which is
+    // stretched over the disc, with their rows centred on it
+    let rows = (height / pixel_size).round().max(1.0);
+    let px = width / (width / pixel_size).round().max(1.0);
+    let py = height / rows;
+    let y = y + ((rows - PATTERN_ROWS) / 2.0).floor() * py;
+    let left = x + px * 2.0;
+    let right = x + width - px * 2.0;
+    let mut square = |sx: f64, sy: f64, sw: f64, sh: f64| {
+        let sw = sw.min(right - sx);
+        if sw > 0.0 {
+            graphics::rectangle(COLOR_PATTERN, [sx, sy, sw, sh], c.transform, gl);
+        }
+    };
+
+    match pattern {
+        // Row of dots
+        0 => {
+            let mut sx = left + px;
+            while sx < right {
+                square(sx, y + py * 5.0, px * 2.0, py * 2.0);
+                sx += px * 5.0;
+            }
+        }
+        // Vertical stripes
+        1 => {
+            let mut sx = left + px;
+            while sx < right {
+                square(sx, y + py * 3.0, px, py * 6.0);
+                sx += px * 3.0;
+            }
+        }
+        // One wide band
+        2 => square(left, y + py * 5.0, right - left, py * 2.0),
+        // Checkerboard
+        3 => {
+            let mut sx = left;
+            let mut i = 0;
+            while sx < right {
+                let row = if i % 2 == 0 { 3.0 } else { 6.0 };
+                square(sx, y + py * row, px * 3.0, py * 3.0);
+                sx += px * 3.0;
+                i += 1;
+            }
+        }
+        // Two thin bands
+        4 => {
+            square(left, y + py * 3.0, right - left, py);
+            square(left, y + py * 8.0, right - left, py);
+        }
+        // Grid of small squares
+        _ => {
+            let mut sx = left + px;
+            while sx < right {
+                square(sx, y + py * 3.0, px, py);
+                square(sx, y + py * 8.0, px, py);
+                sx += px * 2.0;
+            }
+        }
+    }
+}
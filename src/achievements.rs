@@ -0,0 +1,233 @@
+// Star ratings for solved puzzles, and the achievements unlocked by them
+
+use crate::challenge::{optimal_moves, with_slack};
+use crate::game::{Board, Game, Variant, VARIANTS};
+use serde::{Deserialize, Serialize};
+
+// Two stars allow this many percent more moves than the optimal solution
+pub const TWO_STAR_SLACK_PERCENT: u64 = 25;
+
+const FLAWLESS_DISCS: u32 = 4;
+const OCTAVE_DISCS: u32 = 8;
+const NO_UNDO_DISCS: u32 = 6;
+const QUICK_DISCS: u32 = 6;
+const QUICK_SECONDS: f64 = 60.0;
+const TALL_DISCS: u32 = 10;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    FirstTower,
+    Flawless,
+    Octave,
+    NoUndo,
+    Quick,
+    Explorer,
+    TallTower,
+}
+
+pub const ACHIEVEMENTS: [Achievement; 7] = [
+    Achievement::FirstTower,
+    Achievement::Flawless,
+    Achievement::Octave,
+    Achievement::NoUndo,
+    Achievement::Quick,
+    Achievement::Explorer,
+    Achievement::TallTower,
+];
+
+impl Achievement {
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::FirstTower => "First tower",
+            Achievement::Flawless => "Flawless",
+            Achievement::Octave => "Octave",
+            Achievement::NoUndo => "No regrets",
+            Achievement::Quick => "Against the clock",
+            Achievement::Explorer => "Explorer",
+            Achievement::TallTower => "Tall tower",
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Achievement::FirstTower => String::from("Solve a puzzle"),
+            Achievement::Flawless => {
+                format!("Solve {} or more discs in the fewest moves", FLAWLESS_DISCS)
+            }
+            Achievement::Octave => format!("Solve {} discs optimally", OCTAVE_DISCS),
+            Achievement::NoUndo => {
+                format!("Solve {} or more discs without an undo", NO_UNDO_DISCS)
+            }
+            Achievement::Quick => format!(
+                "Solve {} or more discs in under {}s",
+                QUICK_DISCS, QUICK_SECONDS
+            ),
+            Achievement::Explorer => String::from("Try every rule variant"),
+            Achievement::TallTower => format!("Solve {} or more discs", TALL_DISCS),
+        }
+    }
+}
+
+// 3 stars for the optimal solution, 2 for up to 25% more moves, 1 for more
+pub fn rating(moves: u64, optimal: u64) -> u32 {
+    if moves <= optimal {
+        3
+    } else if moves <= with_slack(optimal, TWO_STAR_SLACK_PERCENT) {
+        2
+    } else {
+        1
+    }
+}
+
+// Stars as shown to the player, like `**-`
+pub fn star_text(stars: u32) -> String {
+    format!("{:-<3}", "*".repeat(stars as usize))
+}
+
+// Length of a shortest solution of the game from its start. The usual tower
+// has a known solution even when it is too big to search.
+pub fn shortest_solution(game: &Game) -> Option<u64> {
+    let board = game.board();
+    let usual = *game.start() == Board::new(board.n_discs(), board.n_rods())
+        && game.target() == board.n_rods() - 1;
+    if usual && board.variant() == Variant::Classic {
+        return Some(optimal_moves(board.n_discs(), board.n_rods()));
+    }
+    game.optimal_moves()
+}
+
+// How a puzzle was solved
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solve {
+    pub discs: u32,
+    pub rods: usize,
+    pub variant: Variant,
+    pub moves: u64,
+    // None when the puzzle is too big to know
+    pub optimal: Option<u64>,
+    pub undos: u32,
+    pub seconds: f64,
+}
+
+impl Solve {
+    pub fn new(game: &Game, seconds: f64) -> Solve {
+        let board = game.board();
+        Solve {
+            discs: board.n_discs(),
+            rods: board.n_rods(),
+            variant: board.variant(),
+            moves: game.move_count() as u64,
+            optimal: shortest_solution(game),
+            undos: game.undo_count(),
+            seconds,
+        }
+    }
+
+    pub fn stars(&self) -> Option<u32> {
+        self.optimal.map(|optimal| rating(self.moves, optimal))
+    }
+
+    fn optimal(&self) -> bool {
+        self.stars() == Some(3)
+    }
+
+    // Achievements earned by the solve on its own
+    pub fn earned(&self) -> Vec<Achievement> {
+        let earned = [
+            (Achievement::FirstTower, true),
+            (
+                Achievement::Flawless,
+                self.discs >= FLAWLESS_DISCS && self.optimal(),
+            ),
+            (
+                Achievement::Octave,
+                self.discs >= OCTAVE_DISCS && self.optimal(),
+            ),
+            (
+                Achievement::NoUndo,
+                self.discs >= NO_UNDO_DISCS && self.undos == 0,
+            ),
+            (
+                Achievement::Quick,
+                self.discs >= QUICK_DISCS && self.seconds < QUICK_SECONDS,
+            ),
+            (Achievement::TallTower, self.discs >= TALL_DISCS),
+        ];
+        earned
+            .into_iter()
+            .filter(|(_, earned)| *earned)
+            .map(|(achievement, _)| achievement)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Unlock {
+    pub achievement: Achievement,
+    // Unix time it was unlocked at
+    pub unlocked_at: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Achievements {
+    // Variants played at least one move of
+    #[serde(default)]
+    pub tried: Vec<Variant>,
+    #[serde(default)]
+    pub unlocked: Vec<Unlock>,
+}
+
+impl Achievements {
+    pub fn from_toml(text: &str) -> Result<Achievements, String> {
+        toml::from_str(text).map_err(|err| err.to_string())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("achievements are always valid TOML")
+    }
+
+    pub fn has(&self, achievement: Achievement) -> bool {
+        self.unlocked
+            .iter()
+            .any(|unlock| unlock.achievement == achievement)
+    }
+
+    // Returns whether the achievement is new
+    fn unlock(&mut self, achievement: Achievement, now: u64) -> bool {
+        if self.has(achievement) {
+            return false;
+        }
+        self.unlocked.push(Unlock {
+            achievement,
+            unlocked_at: now,
+        });
+        true
+    }
+
+    fn unlock_all(&mut self, achievements: Vec<Achievement>, now: u64) -> Vec<Achievement> {
+        achievements
+            .into_iter()
+            .filter(|achievement| self.unlock(*achievement, now))
+            .collect()
+    }
+
+    // Notes a move played in `variant`, returning newly unlocked achievements
+    pub fn try_variant(&mut self, variant: Variant, now: u64) -> Vec<Achievement> {
+        if !self.tried.contains(&variant) {
+            self.tried.push(variant);
+        }
+        let explored = VARIANTS.iter().all(|variant| self.tried.contains(variant));
+        let earned = if explored {
+            vec![Achievement::Explorer]
+        } else {
+            vec![]
+        };
+        self.unlock_all(earned, now)
+    }
+
+    // Newly unlocked achievements of a solve
+    pub fn solved(&mut self, solve: &Solve, now: u64) -> Vec<Achievement> {
+        self.unlock_all(solve.earned(), now)
+    }
+}
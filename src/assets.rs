@@ -0,0 +1,181 @@
+use image::RgbaImage;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+const ASSETS_FOLDER: &str = "assets";
+const APP_FOLDER: &str = "hanoi";
+
+// Every image in `assets/`, compiled into the executable, so themes can use
+// any of them without the folder
+#[cfg(feature = "embedded-assets")]
+macro_rules! embed {
+    ($($file:literal),* $(,)?) => {
+        &[$(($file, include_bytes!(concat!("../assets/", $file)))),*]
+    };
+}
+
+#[cfg(feature = "embedded-assets")]
+const EMBEDDED: &[(&str, &[u8])] = embed![
+    "block1.png",
+    "block2_left.png",
+    "block2_middle.png",
+    "block2_right.png",
+    "block_grayscale_highlight_left.png",
+    "block_grayscale_highlight_middle.png",
+    "block_grayscale_highlight_right.png",
+    "block_grayscale_left.png",
+    "block_grayscale_middle.png",
+    "block_grayscale_right.png",
+    "rod1.png",
+    "rod2.png",
+    "rod2_highlight.png",
+];
+
+#[derive(Debug)]
+pub enum AssetError {
+    NotFound {
+        file: String,
+        searched: Vec<PathBuf>,
+    },
+    Decode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::NotFound { file, searched } => {
+                write!(f, "asset '{}' not found (searched", file)?;
+                for dir in searched.iter() {
+                    write!(f, " {}", dir.display())?;
+                }
+                write!(f, ")")
+            }
+            AssetError::Decode { path, source } => {
+                write!(
+                    f,
+                    "asset '{}' could not be read: {}",
+                    path.display(),
+                    source
+                )
+            }
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AssetError::NotFound { .. } => None,
+            AssetError::Decode { source, .. } => Some(source),
+        }
+    }
+}
+
+// Folders that may hold the assets, most specific first
+pub struct Assets {
+    override_dir: Option<PathBuf>,
+    dirs: Vec<PathBuf>,
+}
+
+fn xdg_data_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = vec![];
+
+    match env::var_os("XDG_DATA_HOME") {
+        Some(home) if !home.is_empty() => dirs.push(PathBuf::from(home)),
+        _ => {
+            if let Some(home) = env::var_os("HOME") {
+                dirs.push(Path::new(&home).join(".local/share"));
+            }
+        }
+    }
+
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+    dirs.extend(data_dirs.split(':').map(PathBuf::from));
+
+    dirs.into_iter()
+        .map(|dir| dir.join(APP_FOLDER).join(ASSETS_FOLDER))
+        .collect()
+}
+
+impl Assets {
+    // Looks in the override folder, next to the executable, in the source tree,
+    // in the XDG data folders and finally in the working directory
+    pub fn discover(override_dir: Option<PathBuf>) -> Assets {
+        let mut dirs: Vec<PathBuf> = vec![];
+
+        if let Some(exe_dir) = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            // `target/debug/hanoi` finds the assets two levels up
+            if let Ok(dir) = find_folder::Search::ParentsThenKids(3, 1)
+                .of(exe_dir)
+                .for_folder(ASSETS_FOLDER)
+            {
+                dirs.push(dir);
+            }
+        }
+
+        if let Some(manifest_dir) = env::var_os("CARGO_MANIFEST_DIR") {
+            dirs.push(Path::new(&manifest_dir).join(ASSETS_FOLDER));
+        }
+        dirs.push(Path::new(env!("CARGO_MANIFEST_DIR")).join(ASSETS_FOLDER));
+
+        dirs.extend(xdg_data_dirs());
+        dirs.push(PathBuf::from(".").join(ASSETS_FOLDER));
+
+        dirs.dedup();
+        Assets { override_dir, dirs }
+    }
+
+    fn find(dirs: &[PathBuf], file: &str) -> Option<PathBuf> {
+        dirs.iter()
+            .map(|dir| dir.join(file))
+            .find(|path| path.is_file())
+    }
+
+    fn decode(path: PathBuf) -> Result<RgbaImage, AssetError> {
+        image::open(&path)
+            .map(|image| image.to_rgba8())
+            .map_err(|source| AssetError::Decode { path, source })
+    }
+
+    // An override folder always wins; embedded assets come before any other folder
+    pub fn load_image(&self, file: &str) -> Result<RgbaImage, AssetError> {
+        if let Some(path) = Assets::find(self.override_dir.as_slice(), file) {
+            return Assets::decode(path);
+        }
+
+        #[cfg(feature = "embedded-assets")]
+        if let Some((_, bytes)) = EMBEDDED.iter().find(|(name, _)| *name == file) {
+            return image::load_from_memory(bytes)
+                .map(|image| image.to_rgba8())
+                .map_err(|source| AssetError::Decode {
+                    path: Path::new("embedded").join(file),
+                    source,
+                });
+        }
+
+        if let Some(path) = Assets::find(&self.dirs, file) {
+            return Assets::decode(path);
+        }
+
+        Err(AssetError::NotFound {
+            file: file.to_string(),
+            searched: self
+                .override_dir
+                .iter()
+                .chain(self.dirs.iter())
+                .cloned()
+                .collect(),
+        })
+    }
+}
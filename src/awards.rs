@@ -0,0 +1,202 @@
+use crate::settings::{config_path, read_config, write_config};
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use hanoi::achievements::{star_text, Achievement, Achievements, Solve};
+use hanoi::campaign::Level;
+use hanoi::game::Game;
+use std::collections::VecDeque;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// Ratings and achievements of puzzles solved by hand, the file achievements
+// are kept in, and the toasts announcing them
+
+const ACHIEVEMENTS_FILE: &str = "achievements.toml";
+
+const COLOR_TOAST_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.75];
+const COLOR_TITLE: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_TEXT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+const TITLE_SCALE: f64 = 3.0;
+const TEXT_SCALE: f64 = 2.0;
+const TOAST_PADDING: f64 = 12.0;
+const LINE_GAP: f64 = 8.0;
+// Distance of toasts from the bottom of the board
+const TOAST_MARGIN: f64 = 30.0;
+
+// Seconds every toast stays up, the last of them fading out
+const TOAST_TIME: f64 = 3.0;
+const FADE_TIME: f64 = 0.5;
+
+// A broken achievements file is reported and treated as empty
+pub fn load_achievements() -> Achievements {
+    read_config(ACHIEVEMENTS_FILE, Achievements::from_toml)
+}
+
+fn save_achievements(achievements: &Achievements) {
+    if let Some(path) = config_path(ACHIEVEMENTS_FILE) {
+        write_config(&path, &achievements.to_toml());
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+struct Toast {
+    title: String,
+    text: String,
+}
+
+pub struct Awards {
+    achievements: Achievements,
+    // First move of the puzzle being played
+    started: Option<Instant>,
+    // Hints or the solver were used on the puzzle
+    helped: bool,
+    // The puzzle was solved and rated already
+    rated: bool,
+    toasts: VecDeque<Toast>,
+    // When the first toast went up
+    shown: Option<Instant>,
+}
+
+impl Awards {
+    pub fn new() -> Awards {
+        Awards {
+            achievements: load_achievements(),
+            started: None,
+            helped: false,
+            rated: false,
+            toasts: VecDeque::new(),
+            shown: None,
+        }
+    }
+
+    // Starts tracking a new puzzle
+    pub fn new_puzzle(&mut self) {
+        self.started = None;
+        self.helped = false;
+        self.rated = false;
+    }
+
+    // Helped puzzles are still rated, but unlock nothing
+    pub fn help(&mut self) {
+        self.helped = true;
+    }
+
+    // Checks the game after every move played by hand. Campaign levels are
+    // rated by their own star counts, as on the campaign screen.
+    pub fn moved(&mut self, game: &Game, level: Option<&Level>) {
+        let variant = game.board().variant();
+        let mut unlocked = vec![];
+        let mut changed = false;
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+            changed = !self.achievements.tried.contains(&variant);
+            unlocked = self.achievements.try_variant(variant, now());
+        }
+
+        if game.is_solved() && !self.rated {
+            self.rated = true;
+            let seconds = self
+                .started
+                .map_or(0.0, |started| started.elapsed().as_secs_f64());
+            let solve = Solve::new(game, seconds);
+            let stars = match level {
+                Some(level) => Some(level.stars_for(solve.moves)),
+                None => solve.stars(),
+            };
+            let title = match stars {
+                Some(stars) => format!("Solved  {}", star_text(stars)),
+                None => String::from("Solved"),
+            };
+            let text = match solve.optimal {
+                Some(optimal) => format!("{} moves, best is {}", solve.moves, optimal),
+                None => format!("{} moves", solve.moves),
+            };
+            self.toasts.push_back(Toast { title, text });
+            if !self.helped {
+                unlocked.extend(self.achievements.solved(&solve, now()));
+            }
+        }
+
+        for achievement in &unlocked {
+            self.announce(*achievement);
+        }
+        if changed || !unlocked.is_empty() {
+            save_achievements(&self.achievements);
+        }
+    }
+
+    fn announce(&mut self, achievement: Achievement) {
+        self.toasts.push_back(Toast {
+            title: format!("Achievement: {}", achievement.title()),
+            text: achievement.description(),
+        });
+    }
+
+    // Takes down toasts that have been up long enough
+    pub fn update(&mut self) {
+        if self.toasts.is_empty() {
+            return;
+        }
+        let shown = *self.shown.get_or_insert_with(Instant::now);
+        if shown.elapsed().as_secs_f64() > TOAST_TIME {
+            self.toasts.pop_front();
+            self.shown = None;
+        }
+    }
+
+    // The first toast, centred near the bottom of `area`
+    pub fn render(
+        &self,
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+        area: [f64; 4],
+    ) {
+        let Some(toast) = self.toasts.front() else {
+            return;
+        };
+        let age = self
+            .shown
+            .map_or(0.0, |shown| shown.elapsed().as_secs_f64());
+        let alpha = ((TOAST_TIME - age) / FADE_TIME).clamp(0.0, 1.0) as f32;
+        let fade = |color: [f32; 4]| [color[0], color[1], color[2], color[3] * alpha];
+
+        let width = text_width(&toast.title, TITLE_SCALE).max(text_width(&toast.text, TEXT_SCALE))
+            + TOAST_PADDING * 2.0;
+        let title_height = GLYPH_HEIGHT as f64 * TITLE_SCALE;
+        let height =
+            title_height + LINE_GAP + GLYPH_HEIGHT as f64 * TEXT_SCALE + TOAST_PADDING * 2.0;
+        let x = area[0] + (area[2] - width) / 2.0;
+        let y = area[1] + area[3] - height - TOAST_MARGIN;
+        graphics::rectangle(
+            fade(COLOR_TOAST_BACKGROUND),
+            [x, y, width, height],
+            c.transform,
+            gl,
+        );
+
+        let center = |text: &str, scale| x + (width - text_width(text, scale)) / 2.0;
+        let line_y = y + TOAST_PADDING;
+        draw_text(
+            &toast.title,
+            center(&toast.title, TITLE_SCALE),
+            line_y,
+            TITLE_SCALE,
+            fade(COLOR_TITLE),
+            c,
+            gl,
+        );
+        draw_text(
+            &toast.text,
+            center(&toast.text, TEXT_SCALE),
+            line_y + title_height + LINE_GAP,
+            TEXT_SCALE,
+            fade(COLOR_TEXT),
+            c,
+            gl,
+        );
+    }
+}
@@ -0,0 +1,361 @@
+// Terminal front-end, for machines without an OpenGL window (e.g. over SSH)
+
+use std::io;
+use std::process;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use hanoi::game::{rod_name, Game, Move, MAX_DISCS, MAX_RODS, MIN_RODS};
+use hanoi::solver::Trace;
+use hanoi::theme::Theme;
+
+const N_DISCS: u32 = 5;
+const N_RODS: usize = 3;
+
+const AUTO_SOLVE_DELAY: Duration = Duration::from_millis(400);
+const POLL_TIMEOUT: Duration = Duration::from_millis(50);
+
+const COLOR_ROD: Color = Color::Gray;
+const COLOR_HINT: Color = Color::Yellow;
+
+struct Tui {
+    game: Game,
+    theme: Theme,
+    // Print each disc's size on it, for telling similar colours apart
+    show_numbers: bool,
+    cursor: usize,
+    // Rod the held disc was lifted from
+    held: Option<usize>,
+    hint: Option<Move>,
+    message: String,
+    auto_solve: Option<(Vec<Move>, usize)>,
+    last_step: Instant,
+    quit: bool,
+}
+
+fn disc_color(theme: &Theme, disc: u32, n_discs: u32) -> Color {
+    let [r, g, b] = theme.disc_color(disc, n_discs);
+    Color::Rgb(r, g, b)
+}
+
+impl Tui {
+    fn new(n_discs: u32, n_rods: usize, theme: Theme) -> Tui {
+        Tui {
+            game: Game::new(n_discs, n_rods),
+            theme,
+            show_numbers: false,
+            cursor: 0,
+            held: None,
+            hint: None,
+            message: String::new(),
+            auto_solve: None,
+            last_step: Instant::now(),
+            quit: false,
+        }
+    }
+
+    fn key_pressed(&mut self, code: KeyCode) {
+        // Only stopping and quitting are allowed while the solver plays
+        if self.auto_solve.is_some() {
+            match code {
+                KeyCode::Char('s') | KeyCode::Esc => {
+                    self.auto_solve = None;
+                    self.message = String::from("Auto-solve stopped");
+                }
+                KeyCode::Char('q') => self.quit = true,
+                _ => {}
+            }
+            return;
+        }
+
+        let n_rods = self.game.board().n_rods();
+        match code {
+            KeyCode::Left => self.cursor = (self.cursor + n_rods - 1) % n_rods,
+            KeyCode::Right => self.cursor = (self.cursor + 1) % n_rods,
+            KeyCode::Char(c @ '1'..='9') => {
+                let rod = c as usize - '1' as usize;
+                if rod < n_rods {
+                    self.cursor = rod;
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => self.pick_or_place(),
+            KeyCode::Esc => self.held = None,
+            KeyCode::Char('u') => {
+                self.held = None;
+                self.hint = None;
+                if self.game.undo().is_none() {
+                    self.message = String::from("Nothing to undo");
+                }
+            }
+            KeyCode::Char('r') => {
+                self.held = None;
+                self.hint = None;
+                if self.game.redo().is_none() {
+                    self.message = String::from("Nothing to redo");
+                }
+            }
+            KeyCode::Char('h') => {
+                self.hint = self.game.hint();
+                self.message = match self.hint {
+                    Some(mv) => format!(
+                        "Hint: move disc {} from {} to {}",
+                        mv.disc + 1,
+                        rod_name(mv.from),
+                        rod_name(mv.to)
+                    ),
+                    None => String::from("No hint available"),
+                };
+            }
+            KeyCode::Char('s') => self.start_auto_solve(),
+            KeyCode::Char('l') => self.show_numbers = !self.show_numbers,
+            KeyCode::Char('n') => {
+                self.game.restart();
+                self.held = None;
+                self.hint = None;
+                self.message = String::from("New game");
+            }
+            KeyCode::Char('q') => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn pick_or_place(&mut self) {
+        match self.held {
+            None => {
+                if self.game.board().top(self.cursor).is_some() {
+                    self.held = Some(self.cursor);
+                    self.message.clear();
+                } else {
+                    self.message = format!("Rod {} is empty", rod_name(self.cursor));
+                }
+            }
+            Some(from) if from == self.cursor => self.held = None,
+            Some(from) => {
+                self.held = None;
+                match self.game.try_move(from, self.cursor) {
+                    Ok(_) => {
+                        self.hint = None;
+                        self.message = if self.game.is_solved() {
+                            format!("Solved in {} moves!", self.game.move_count())
+                        } else {
+                            String::new()
+                        };
+                    }
+                    Err(err) => self.message = format!("Can't move there: {}", err),
+                }
+            }
+        }
+    }
+
+    // Restarts the puzzle and plays the recursive solution
+    fn start_auto_solve(&mut self) {
+        self.game.restart();
+        self.held = None;
+        self.hint = None;
+
+        let trace = Trace::new(self.game.board().n_discs(), 0, self.game.target(), 1);
+        self.auto_solve = Some((trace.moves, 0));
+        self.last_step = Instant::now();
+        self.message = String::from("Auto-solving...");
+    }
+
+    fn update(&mut self) {
+        if let Some((moves, next)) = &mut self.auto_solve {
+            if self.last_step.elapsed() < AUTO_SOLVE_DELAY {
+                return;
+            }
+            self.last_step = Instant::now();
+
+            let mv = moves[*next];
+            self.game
+                .try_move(mv.from, mv.to)
+                .expect("solver made an illegal move");
+            *next += 1;
+
+            if *next == moves.len() {
+                self.auto_solve = None;
+                self.message = format!("Solved in {} moves!", self.game.move_count());
+            }
+        }
+    }
+
+    fn disc_span(&self, disc: u32, column_width: usize) -> Vec<Span<'static>> {
+        let width = disc as usize * 2 + 3;
+        let pad = " ".repeat((column_width - width) / 2);
+        let color = disc_color(&self.theme, disc, self.game.board().n_discs());
+        let block = if self.show_numbers {
+            let number = (disc + 1).to_string();
+            let left = (width - number.len()) / 2;
+            let right = width - number.len() - left;
+            Span::styled(
+                format!("{}{}{}", " ".repeat(left), number, " ".repeat(right)),
+                Style::default()
+                    .bg(color)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled("█".repeat(width), Style::default().fg(color))
+        };
+        vec![Span::raw(pad.clone()), block, Span::raw(pad)]
+    }
+
+    fn rod_span(column_width: usize) -> Vec<Span<'static>> {
+        let pad = " ".repeat((column_width - 1) / 2);
+        vec![
+            Span::raw(pad.clone()),
+            Span::styled("┃", Style::default().fg(COLOR_ROD)),
+            Span::raw(pad),
+        ]
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let board = self.game.board();
+        let n_discs = board.n_discs();
+        let column_width = n_discs as usize * 2 + 3;
+        let empty = " ".repeat(column_width);
+
+        let mut lines: Vec<Line> = vec![];
+
+        // Held disc floats above the selected rod
+        let mut held_row: Vec<Span> = vec![];
+        for rod in 0..board.n_rods() {
+            match self.held {
+                Some(from) if rod == self.cursor => {
+                    held_row.extend(self.disc_span(board.top(from).unwrap(), column_width))
+                }
+                _ => held_row.push(Span::raw(empty.clone())),
+            }
+            held_row.push(Span::raw(" "));
+        }
+        lines.push(Line::from(held_row));
+
+        for level in (0..=n_discs as usize).rev() {
+            let mut row: Vec<Span> = vec![];
+            for (i_rod, rod) in board.rods().iter().enumerate() {
+                let lifted = self.held == Some(i_rod) && level == rod.len() - 1;
+                match rod.get(level) {
+                    Some(disc) if !lifted => row.extend(self.disc_span(*disc, column_width)),
+                    _ => row.extend(Tui::rod_span(column_width)),
+                }
+                row.push(Span::raw(" "));
+            }
+            lines.push(Line::from(row));
+        }
+
+        lines.push(Line::from(Span::styled(
+            "▀".repeat((column_width + 1) * board.n_rods() - 1),
+            Style::default().fg(COLOR_ROD),
+        )));
+
+        // Rod names, with the cursor and the current hint marked
+        let mut labels: Vec<Span> = vec![];
+        for rod in 0..board.n_rods() {
+            let pad = " ".repeat((column_width - 1) / 2);
+            let mut style = Style::default();
+            if self
+                .hint
+                .is_some_and(|hint| hint.from == rod || hint.to == rod)
+            {
+                style = style.fg(COLOR_HINT);
+            }
+            if rod == self.cursor {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            labels.push(Span::raw(pad.clone()));
+            labels.push(Span::styled(rod_name(rod).to_string(), style));
+            labels.push(Span::raw(pad));
+            labels.push(Span::raw(" "));
+        }
+        lines.push(Line::from(labels));
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(format!(
+            "Moves: {}    Target: rod {}",
+            self.game.move_count(),
+            rod_name(self.game.target())
+        )));
+        lines.push(Line::from(self.message.clone()));
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "←/→ or 1-9 select  space pick/place  esc cancel  u undo  r redo  h hint  s solve  l numbers  n new  q quit",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .block(Block::bordered().title(" Torri di Hanoi ")),
+            frame.area(),
+        );
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, tui: &mut Tui) -> io::Result<()> {
+    while !tui.quit {
+        terminal.draw(|frame| tui.draw(frame))?;
+
+        if event::poll(POLL_TIMEOUT)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    tui.key_pressed(key.code);
+                }
+            }
+        }
+
+        tui.update();
+    }
+    Ok(())
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: hanoi-tui [--discs N] [--rods K] [--theme NAME|FILE]\n  \
+         N is from 1 to {}, K from {} to {}",
+        MAX_DISCS, MIN_RODS, MAX_RODS
+    );
+    process::exit(2);
+}
+
+fn parse_args() -> (u32, usize, Theme) {
+    let mut n_discs = N_DISCS;
+    let mut n_rods = N_RODS;
+    let mut theme = Theme::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--discs" => n_discs = value.parse().unwrap_or_else(|_| usage()),
+            "--rods" => n_rods = value.parse().unwrap_or_else(|_| usage()),
+            "--theme" => {
+                theme = Theme::load(&value).unwrap_or_else(|err| {
+                    eprintln!("hanoi-tui: {}", err);
+                    process::exit(2);
+                })
+            }
+            _ => usage(),
+        }
+    }
+
+    if !(1..=MAX_DISCS).contains(&n_discs) || !(MIN_RODS..=MAX_RODS).contains(&n_rods) {
+        usage();
+    }
+    (n_discs, n_rods, theme)
+}
+
+fn main() -> io::Result<()> {
+    let (n_discs, n_rods, theme) = parse_args();
+    let mut tui = Tui::new(n_discs, n_rods, theme);
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut tui);
+    ratatui::restore();
+    result
+}
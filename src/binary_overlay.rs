@@ -0,0 +1,99 @@
+use crate::text::{draw_text, GLYPH_HEIGHT};
+use hanoi::game::State;
+
+// In the optimal solution move k moves the disc given by the lowest set bit of k,
+// and the Gray code of k has a bit flipped for every disc that has moved an odd number of times
+
+const COLOR_TEXT: [f32; 4] = [0.85, 0.85, 0.85, 1.0];
+const COLOR_MATCH: [f32; 4] = [0.2, 0.9, 0.4, 1.0];
+const COLOR_MISMATCH: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+
+const TEXT_SCALE: f64 = 2.0;
+const LINE_HEIGHT: f64 = GLYPH_HEIGHT as f64 * TEXT_SCALE + 6.0;
+
+fn to_binary(value: u64, digits: u32) -> String {
+    format!("{:0width$b}", value, width = digits as usize)
+}
+
+fn gray_code(value: u64) -> u64 {
+    value ^ (value >> 1)
+}
+
+// Disc moved by move k (counting from 1), smallest disc being 0
+fn predicted_disc(k: u64) -> u32 {
+    k.trailing_zeros()
+}
+
+fn moved_disc(before: &[usize], after: &[usize]) -> Option<u32> {
+    before
+        .iter()
+        .zip(after.iter())
+        .position(|(a, b)| a != b)
+        .map(|disc| disc as u32)
+}
+
+pub fn render(
+    c: graphics::Context,
+    gl: &mut opengl_graphics::GlGraphics,
+    x: f64,
+    y: f64,
+    history: &[State],
+) {
+    let n_discs = history[0].len() as u32;
+    let k = (history.len() - 1) as u64;
+
+    draw_text(
+        &format!(
+            "MOVE {}  BIN {}  GRAY {}",
+            k,
+            to_binary(k, n_discs),
+            to_binary(gray_code(k), n_discs)
+        ),
+        x,
+        y,
+        TEXT_SCALE,
+        COLOR_TEXT,
+        c,
+        gl,
+    );
+
+    draw_text(
+        &format!(
+            "NEXT: LOWEST SET BIT OF {} = {}, MOVE DISC {}",
+            to_binary(k + 1, n_discs),
+            predicted_disc(k + 1),
+            predicted_disc(k + 1) + 1
+        ),
+        x,
+        y + LINE_HEIGHT,
+        TEXT_SCALE,
+        COLOR_TEXT,
+        c,
+        gl,
+    );
+
+    if k > 0 {
+        if let Some(actual) = moved_disc(&history[k as usize - 1], &history[k as usize]) {
+            let predicted = predicted_disc(k);
+            let (verdict, color) = if actual == predicted {
+                ("MATCH", COLOR_MATCH)
+            } else {
+                ("DIFFERENT", COLOR_MISMATCH)
+            };
+            draw_text(
+                &format!(
+                    "LAST: RULE SAID DISC {}, YOU MOVED DISC {}  {}",
+                    predicted + 1,
+                    actual + 1,
+                    verdict
+                ),
+                x,
+                y + LINE_HEIGHT * 2.0,
+                TEXT_SCALE,
+                color,
+                c,
+                gl,
+            );
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use piston::input::{Button, HatState, Key, MouseButton};
+use std::fs;
+use std::path::Path;
+
+// One table of what every key, mouse and gamepad button does, so all of them
+// drive the same actions and any of them can be remapped from a bindings file
+
+// Button numbers of a standard (SDL game controller layout) gamepad
+pub const PAD_A: u8 = 0;
+pub const PAD_B: u8 = 1;
+pub const PAD_Y: u8 = 3;
+pub const PAD_BACK: u8 = 4;
+pub const PAD_START: u8 = 6;
+pub const PAD_LEFT_SHOULDER: u8 = 9;
+pub const PAD_RIGHT_SHOULDER: u8 = 10;
+pub const PAD_DPAD_UP: u8 = 11;
+pub const PAD_DPAD_DOWN: u8 = 12;
+pub const PAD_DPAD_LEFT: u8 = 13;
+pub const PAD_DPAD_RIGHT: u8 = 14;
+
+const PAD_BUTTONS: [(&str, u8); 15] = [
+    ("a", PAD_A),
+    ("b", PAD_B),
+    ("x", 2),
+    ("y", PAD_Y),
+    ("back", PAD_BACK),
+    ("guide", 5),
+    ("start", PAD_START),
+    ("left_stick", 7),
+    ("right_stick", 8),
+    ("left_shoulder", PAD_LEFT_SHOULDER),
+    ("right_shoulder", PAD_RIGHT_SHOULDER),
+    ("dpad_up", PAD_DPAD_UP),
+    ("dpad_down", PAD_DPAD_DOWN),
+    ("dpad_left", PAD_DPAD_LEFT),
+    ("dpad_right", PAD_DPAD_RIGHT),
+];
+
+const HATS: [(&str, HatState); 4] = [
+    ("up", HatState::Up),
+    ("down", HatState::Down),
+    ("left", HatState::Left),
+    ("right", HatState::Right),
+];
+
+// Left stick position that counts as pushing it sideways, and the position it
+// must come back to before it selects again
+pub const STICK_AXIS: u8 = 0;
+pub const STICK_PUSH: f64 = 0.5;
+pub const STICK_RELEASE: f64 = 0.25;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    SelectLeft,
+    SelectRight,
+    // Only used by menus
+    SelectUp,
+    SelectDown,
+    // Picks up the top disc of the selected rod, or puts the held one down on it
+    PickOrPlace,
+    Cancel,
+    Undo,
+    Redo,
+    Hint,
+    Solve,
+    Restart,
+    Pause,
+    Quit,
+    Settings,
+    ToggleFullscreen,
+    NextTheme,
+    CycleLabels,
+    ToggleHighContrast,
+    ToggleStateGraph,
+    ToggleBinaryOverlay,
+    ExportSvg,
+    Speedrun,
+    MoveChallenge,
+    TimeChallenge,
+    ParCourse,
+    Campaign,
+}
+
+// Names of the actions in a bindings file
+const ACTIONS: [(&str, Action); 26] = [
+    ("select_left", Action::SelectLeft),
+    ("select_right", Action::SelectRight),
+    ("select_up", Action::SelectUp),
+    ("select_down", Action::SelectDown),
+    ("pick_place", Action::PickOrPlace),
+    ("cancel", Action::Cancel),
+    ("undo", Action::Undo),
+    ("redo", Action::Redo),
+    ("hint", Action::Hint),
+    ("solve", Action::Solve),
+    ("restart", Action::Restart),
+    ("pause", Action::Pause),
+    ("quit", Action::Quit),
+    ("settings", Action::Settings),
+    ("fullscreen", Action::ToggleFullscreen),
+    ("next_theme", Action::NextTheme),
+    ("cycle_labels", Action::CycleLabels),
+    ("high_contrast", Action::ToggleHighContrast),
+    ("state_graph", Action::ToggleStateGraph),
+    ("binary_overlay", Action::ToggleBinaryOverlay),
+    ("export_svg", Action::ExportSvg),
+    ("speedrun", Action::Speedrun),
+    ("move_challenge", Action::MoveChallenge),
+    ("time_challenge", Action::TimeChallenge),
+    ("par_course", Action::ParCourse),
+    ("campaign", Action::Campaign),
+];
+
+fn action_name(action: Action) -> &'static str {
+    ACTIONS.iter().find(|(_, a)| *a == action).unwrap().0
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    Gamepad(u8),
+    Hat(HatState),
+}
+
+impl Binding {
+    // Controllers are told apart by id, but bindings apply to all of them
+    pub fn from_button(button: &Button) -> Binding {
+        match button {
+            Button::Keyboard(key) => Binding::Key(*key),
+            Button::Mouse(button) => Binding::Mouse(*button),
+            Button::Controller(button) => Binding::Gamepad(button.button),
+            Button::Hat(hat) => Binding::Hat(hat.state),
+        }
+    }
+
+    // `Space` or `key:Space`, `mouse:Left`, `pad:a` or `pad:9`, `hat:left`
+    pub fn parse(name: &str) -> Option<Binding> {
+        let (kind, value) = name.split_once(':').unwrap_or(("key", name));
+        let piston_name = || toml::Value::String(value.to_string());
+        match kind {
+            "key" => piston_name().try_into().ok().map(Binding::Key),
+            "mouse" => piston_name().try_into().ok().map(Binding::Mouse),
+            "pad" => PAD_BUTTONS
+                .iter()
+                .find(|(pad_name, _)| *pad_name == value)
+                .map(|(_, button)| *button)
+                .or_else(|| value.parse().ok())
+                .map(Binding::Gamepad),
+            "hat" => HATS
+                .iter()
+                .find(|(hat_name, _)| *hat_name == value)
+                .map(|(_, state)| Binding::Hat(*state)),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(button) => format!("mouse:{:?}", button),
+            Binding::Gamepad(button) => match PAD_BUTTONS.iter().find(|(_, b)| b == button) {
+                Some((name, _)) => format!("pad:{}", name),
+                None => format!("pad:{}", button),
+            },
+            Binding::Hat(state) => format!("hat:{:?}", state).to_lowercase(),
+        }
+    }
+}
+
+pub struct Bindings {
+    pub table: Vec<(Binding, Action)>,
+}
+
+impl Bindings {
+    // Every action listed in the file replaces its default bindings, the rest
+    // keep theirs. All problems are collected so they can be fixed in one go.
+    pub fn from_toml(text: &str) -> Result<Bindings, Vec<String>> {
+        let file: toml::Table = toml::from_str(text).map_err(|err| vec![err.to_string()])?;
+        let mut bindings = Bindings::default();
+        let mut problems = Vec::new();
+
+        for (name, value) in &file {
+            let Some((_, action)) = ACTIONS.iter().find(|(action, _)| action == name) else {
+                problems.push(format!("unknown action '{}'", name));
+                continue;
+            };
+            let names = match value {
+                toml::Value::String(name) => vec![name.as_str()],
+                toml::Value::Array(names) => names.iter().filter_map(|n| n.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            if names.is_empty() && !value.as_array().is_some_and(|a| a.is_empty()) {
+                problems.push(format!("'{}' must be a list of inputs", name));
+                continue;
+            }
+
+            bindings.table.retain(|(_, a)| a != action);
+            for input in names {
+                match Binding::parse(input) {
+                    Some(binding) => bindings.table.push((binding, *action)),
+                    None => problems.push(format!("unknown input '{}' for '{}'", input, name)),
+                }
+            }
+        }
+
+        for (i, (binding, action)) in bindings.table.iter().enumerate() {
+            let earlier = bindings.table[..i].iter().find(|(b, _)| b == binding);
+            if let Some((_, other)) = earlier.filter(|(_, other)| other != action) {
+                problems.push(format!(
+                    "{} is bound to both '{}' and '{}'",
+                    binding.name(),
+                    action_name(*other),
+                    action_name(*action)
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(bindings)
+        } else {
+            Err(problems)
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Bindings, Vec<String>> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| vec![format!("could not read {}: {}", path.display(), err)])?;
+        Bindings::from_toml(&text)
+    }
+
+    pub fn action(&self, binding: Binding) -> Option<Action> {
+        self.table
+            .iter()
+            .find(|(b, _)| *b == binding)
+            .map(|(_, action)| *action)
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Action::*;
+        Bindings {
+            table: vec![
+                (Binding::Mouse(MouseButton::Left), PickOrPlace),
+                (Binding::Key(Key::Left), SelectLeft),
+                (Binding::Key(Key::Right), SelectRight),
+                (Binding::Key(Key::Up), SelectUp),
+                (Binding::Key(Key::Down), SelectDown),
+                (Binding::Key(Key::Space), PickOrPlace),
+                (Binding::Key(Key::Return), PickOrPlace),
+                (Binding::Key(Key::Backspace), Cancel),
+                (Binding::Key(Key::Z), Undo),
+                (Binding::Key(Key::Y), Redo),
+                (Binding::Key(Key::H), Hint),
+                (Binding::Key(Key::S), Solve),
+                (Binding::Key(Key::R), Restart),
+                (Binding::Key(Key::P), Pause),
+                (Binding::Key(Key::Escape), Quit),
+                (Binding::Key(Key::O), Settings),
+                (Binding::Key(Key::F11), ToggleFullscreen),
+                (Binding::Key(Key::T), NextTheme),
+                (Binding::Key(Key::L), CycleLabels),
+                (Binding::Key(Key::C), ToggleHighContrast),
+                (Binding::Key(Key::G), ToggleStateGraph),
+                (Binding::Key(Key::B), ToggleBinaryOverlay),
+                (Binding::Key(Key::E), ExportSvg),
+                (Binding::Key(Key::F5), Speedrun),
+                (Binding::Key(Key::F6), MoveChallenge),
+                (Binding::Key(Key::F7), TimeChallenge),
+                (Binding::Key(Key::F8), ParCourse),
+                (Binding::Key(Key::F9), Campaign),
+                (Binding::Gamepad(PAD_DPAD_LEFT), SelectLeft),
+                (Binding::Gamepad(PAD_DPAD_RIGHT), SelectRight),
+                (Binding::Gamepad(PAD_DPAD_UP), SelectUp),
+                (Binding::Gamepad(PAD_DPAD_DOWN), SelectDown),
+                (Binding::Hat(HatState::Left), SelectLeft),
+                (Binding::Hat(HatState::Right), SelectRight),
+                (Binding::Hat(HatState::Up), SelectUp),
+                (Binding::Hat(HatState::Down), SelectDown),
+                (Binding::Gamepad(PAD_A), PickOrPlace),
+                (Binding::Gamepad(PAD_B), Cancel),
+                (Binding::Gamepad(PAD_LEFT_SHOULDER), Undo),
+                (Binding::Gamepad(PAD_RIGHT_SHOULDER), Redo),
+                (Binding::Gamepad(PAD_Y), Hint),
+                (Binding::Gamepad(PAD_START), Pause),
+                (Binding::Gamepad(PAD_BACK), Settings),
+            ],
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use hanoi::game::{rod_name, Move};
+use hanoi::solver::{Step, Trace};
+
+// Seconds spent on each step of the animation
+const MOVE_DELAY: f64 = 0.5;
+const CALL_DELAY: f64 = 0.15;
+
+const COLOR_PANEL_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const COLOR_TITLE: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const COLOR_ACTIVE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const COLOR_COMPLETED: [f32; 4] = [0.45, 0.45, 0.45, 1.0];
+const COLOR_CURRENT: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_CURRENT_BACKGROUND: [f32; 4] = [0.3, 0.25, 0.05, 1.0];
+
+const TEXT_SCALE: f64 = 2.0;
+const LINE_HEIGHT: f64 = GLYPH_HEIGHT as f64 * TEXT_SCALE + 6.0;
+const PANEL_PADDING: f64 = 10.0;
+
+pub struct AutoSolve {
+    trace: Trace,
+    // Index of the next step to play
+    step: usize,
+    timer: f64,
+    // Frames currently on the call stack, innermost last
+    stack: Vec<usize>,
+    completed: Vec<bool>,
+}
+
+impl AutoSolve {
+    pub fn new(trace: Trace) -> AutoSolve {
+        let completed = vec![false; trace.frames.len()];
+        AutoSolve {
+            trace,
+            step: 0,
+            timer: 0.0,
+            stack: vec![],
+            completed,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.step >= self.trace.steps.len()
+    }
+
+    // Advances the animation, returning the disc moves to apply to the board
+    pub fn update(&mut self, dt: f64) -> Vec<Move> {
+        let mut moves: Vec<Move> = vec![];
+        self.timer += dt;
+
+        while !self.finished() {
+            let delay = match self.trace.steps[self.step] {
+                Step::Move(_) => MOVE_DELAY,
+                _ => CALL_DELAY,
+            };
+            if self.timer < delay {
+                break;
+            }
+            self.timer -= delay;
+
+            match self.trace.steps[self.step] {
+                Step::Enter(frame) => self.stack.push(frame),
+                Step::Move(frame) => {
+                    moves.push(self.trace.moves[self.trace.frames[frame].own_move])
+                }
+                Step::Exit(frame) => {
+                    self.stack.pop();
+                    self.completed[frame] = true;
+                }
+            }
+            self.step += 1;
+        }
+
+        moves
+    }
+
+    fn entered(&self, frame: usize) -> bool {
+        self.completed[frame] || self.stack.contains(&frame)
+    }
+
+    // Completed frames are collapsed, so their children are hidden
+    fn visible(&self, frame: usize) -> bool {
+        self.entered(frame)
+            && self.trace.frames[frame]
+                .parent
+                .is_none_or(|parent| !self.completed[parent])
+    }
+
+    // Number of moves already played
+    fn moves_done(&self) -> usize {
+        self.trace.steps[..self.step]
+            .iter()
+            .filter(|step| matches!(step, Step::Move(_)))
+            .count()
+    }
+
+    pub fn render(
+        &self,
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+        x: f64,
+        y: f64,
+        width: f64,
+    ) {
+        let lines: Vec<usize> = (0..self.trace.frames.len())
+            .filter(|frame| self.visible(*frame))
+            .collect();
+        let moves_done = self.moves_done();
+
+        let height = PANEL_PADDING * 2.0 + LINE_HEIGHT * (lines.len() + 1) as f64;
+        graphics::rectangle(
+            COLOR_PANEL_BACKGROUND,
+            [x, y, width, height],
+            c.transform,
+            gl,
+        );
+
+        let text_x = x + PANEL_PADDING;
+        let mut line_y = y + PANEL_PADDING;
+        draw_text(
+            &format!("CALL TREE  {}/{}", moves_done, self.trace.moves.len()),
+            text_x,
+            line_y,
+            TEXT_SCALE,
+            COLOR_TITLE,
+            c,
+            gl,
+        );
+
+        for frame_i in lines {
+            line_y += LINE_HEIGHT;
+            let frame = &self.trace.frames[frame_i];
+
+            let current = self.stack.last() == Some(&frame_i);
+            let color = if current {
+                graphics::rectangle(
+                    COLOR_CURRENT_BACKGROUND,
+                    [x, line_y - 3.0, width, LINE_HEIGHT],
+                    c.transform,
+                    gl,
+                );
+                COLOR_CURRENT
+            } else if self.completed[frame_i] {
+                COLOR_COMPLETED
+            } else {
+                COLOR_ACTIVE
+            };
+
+            let indent = "  ".repeat(frame.depth as usize);
+            draw_text(
+                &format!("{}{}", indent, frame.call()),
+                text_x,
+                line_y,
+                TEXT_SCALE,
+                color,
+                c,
+                gl,
+            );
+
+            // Link every call to the moves it emitted
+            let moves = if self.completed[frame_i] {
+                format!("#{}-{}", frame.first_move + 1, frame.last_move + 1)
+            } else if frame.own_move < moves_done {
+                let own = &self.trace.moves[frame.own_move];
+                format!(
+                    "#{} {}>{}",
+                    frame.own_move + 1,
+                    rod_name(own.from),
+                    rod_name(own.to)
+                )
+            } else {
+                String::new()
+            };
+            draw_text(
+                &moves,
+                x + width - PANEL_PADDING - text_width(&moves, TEXT_SCALE),
+                line_y,
+                TEXT_SCALE,
+                color,
+                c,
+                gl,
+            );
+        }
+    }
+}
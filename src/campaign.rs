@@ -0,0 +1,210 @@
+// The campaign: an ordered list of levels read from a data file, unlocked
+// one after another, and the progress made through them
+
+use crate::game::{parse_rod, rod_name, Board, Game, State, Variant, MAX_RODS, MIN_RODS};
+use crate::layout::MAX_BOARD_DISCS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const BUILTIN_CAMPAIGN: &str = include_str!("../campaign/campaign.toml");
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    pub name: String,
+    pub discs: u32,
+    pub rods: usize,
+    #[serde(default)]
+    pub variant: Variant,
+    // Rod letter of every disc, smallest first, like "AAB"; all on A when missing
+    pub start: Option<String>,
+    // Rod letter the tower has to end up on; the last rod when missing
+    pub target: Option<char>,
+    // The level is failed when it takes more moves than this
+    pub move_limit: Option<u64>,
+    // Most moves for three, two and one stars
+    pub stars: [u64; 3],
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CampaignFile {
+    levels: Vec<Level>,
+}
+
+pub struct Campaign {
+    pub levels: Vec<Level>,
+}
+
+impl Level {
+    fn start_state(&self) -> Option<State> {
+        match &self.start {
+            Some(start) => start.chars().map(parse_rod).collect(),
+            None => Some(vec![0; self.discs as usize]),
+        }
+    }
+
+    fn target_rod(&self) -> Option<usize> {
+        match self.target {
+            Some(target) => parse_rod(target),
+            None => Some(self.rods - 1),
+        }
+    }
+
+    // Only valid levels can be played, see `Campaign::from_toml`
+    pub fn game(&self) -> Game {
+        let board = Board::with_variant(&self.start_state().unwrap(), self.rods, self.variant);
+        Game::from_board(board, self.target_rod().unwrap())
+    }
+
+    // Stars earned by solving the level in `moves` moves
+    pub fn stars_for(&self, moves: u64) -> u32 {
+        self.stars
+            .iter()
+            .position(|most| moves <= *most)
+            .map_or(0, |i| 3 - i as u32)
+    }
+
+    // Everything wrong with the level
+    fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        // Levels are played on the board, so their towers must fit on it
+        if !(1..=MAX_BOARD_DISCS).contains(&self.discs) {
+            problems.push(format!("discs must be from 1 to {}", MAX_BOARD_DISCS));
+        }
+        if !(MIN_RODS..=MAX_RODS).contains(&self.rods) {
+            problems.push(format!("rods must be from {} to {}", MIN_RODS, MAX_RODS));
+            return problems;
+        }
+        let last = rod_name(self.rods - 1);
+
+        match self.start_state() {
+            Some(state) if state.len() != self.discs as usize => problems.push(format!(
+                "start must give the rod of all {} discs",
+                self.discs
+            )),
+            Some(state) if state.iter().any(|rod| *rod >= self.rods) => {
+                problems.push(format!("start can only use rods A to {}", last))
+            }
+            Some(_) => {}
+            None => problems.push(String::from("start must be rod letters like AAB")),
+        }
+        if self.target_rod().is_none_or(|target| target >= self.rods) {
+            problems.push(format!("target must be a rod from A to {}", last));
+        }
+        if !self.stars.is_sorted() {
+            problems.push(String::from(
+                "stars must be the most moves for three, two and one stars, in order",
+            ));
+        }
+        if self.move_limit.is_some_and(|limit| limit < self.stars[2]) {
+            problems.push(String::from("the move limit leaves no room for one star"));
+        }
+        if !problems.is_empty() {
+            return problems;
+        }
+
+        // Levels that are too big to search are trusted to be solvable
+        let game = self.game();
+        if game.is_solved() {
+            problems.push(String::from("the level starts solved"));
+        }
+        if let Some(optimal) = game.moves_to_solve() {
+            if self.stars[0] < optimal {
+                problems.push(format!(
+                    "three stars need at least {} moves, the shortest solution",
+                    optimal
+                ));
+            }
+        } else if self.variant != Variant::Classic {
+            problems.push(String::from(
+                "the level is too big to check it can be solved",
+            ));
+        }
+        problems
+    }
+}
+
+impl Campaign {
+    // All problems are collected so they can be fixed in one go
+    pub fn from_toml(text: &str) -> Result<Campaign, Vec<String>> {
+        let file: CampaignFile = toml::from_str(text).map_err(|err| vec![err.to_string()])?;
+        let mut problems = vec![];
+        if file.levels.is_empty() {
+            problems.push(String::from("there are no levels"));
+        }
+        for (i, level) in file.levels.iter().enumerate() {
+            for problem in level.problems() {
+                problems.push(format!("level {} '{}': {}", i + 1, level.name, problem));
+            }
+            // Progress is kept by name
+            if file.levels[..i]
+                .iter()
+                .any(|other| other.name == level.name)
+            {
+                problems.push(format!(
+                    "level {}: the name '{}' is used twice",
+                    i + 1,
+                    level.name
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(Campaign {
+                levels: file.levels,
+            })
+        } else {
+            Err(problems)
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Campaign, Vec<String>> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| vec![format!("could not read {}: {}", path.display(), err)])?;
+        Campaign::from_toml(&text)
+    }
+
+    pub fn builtin() -> Campaign {
+        Campaign::from_toml(BUILTIN_CAMPAIGN).expect("built-in campaign is invalid")
+    }
+}
+
+// Best stars of every level solved so far, by level name
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Progress {
+    pub stars: BTreeMap<String, u32>,
+}
+
+impl Progress {
+    pub fn from_toml(text: &str) -> Result<Progress, String> {
+        toml::from_str(text).map_err(|err| err.to_string())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("progress is always valid TOML")
+    }
+
+    pub fn solved(&self, level: &Level) -> bool {
+        self.stars.contains_key(&level.name)
+    }
+
+    // Keeps the best stars of every level
+    pub fn complete(&mut self, level: &Level, stars: u32) {
+        let best = self.stars.entry(level.name.clone()).or_insert(stars);
+        *best = (*best).max(stars);
+    }
+
+    // Levels that can be played: the first one, and every one after a solved level
+    pub fn unlocked(&self, campaign: &Campaign) -> usize {
+        let solved = campaign
+            .levels
+            .iter()
+            .take_while(|level| self.solved(level))
+            .count();
+        (solved + 1).min(campaign.levels.len())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::settings::{config_path, read_config, write_config};
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use hanoi::achievements::star_text;
+use hanoi::campaign::{Campaign, Progress};
+use hanoi::game::Variant;
+
+// Level select, drawn over the board, and the progress file behind it
+
+const PROGRESS_FILE: &str = "progress.toml";
+
+const COLOR_PANEL_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.85];
+const COLOR_TITLE: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const COLOR_ITEM: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const COLOR_LOCKED: [f32; 4] = [0.4, 0.4, 0.4, 1.0];
+const COLOR_STARS: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_SELECTED: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_SELECTED_BACKGROUND: [f32; 4] = [0.3, 0.25, 0.05, 1.0];
+
+const TEXT_SCALE: f64 = 2.0;
+const LINE_HEIGHT: f64 = GLYPH_HEIGHT as f64 * TEXT_SCALE + 10.0;
+const PANEL_WIDTH: f64 = 640.0;
+const PANEL_PADDING: f64 = 20.0;
+// Space above a row's text inside its highlight
+const ROW_PADDING: f64 = 5.0;
+
+// A broken progress file is reported and treated as empty
+pub fn load_progress() -> Progress {
+    read_config(PROGRESS_FILE, Progress::from_toml)
+}
+
+pub fn save_progress(progress: &Progress) {
+    if let Some(path) = config_path(PROGRESS_FILE) {
+        write_config(&path, &progress.to_toml());
+    }
+}
+
+pub fn variant_name(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Classic => "classic",
+        Variant::Cyclic => "cyclic",
+        Variant::Adjacent => "adjacent",
+        Variant::Magnetic => "magnetic",
+    }
+}
+
+pub struct CampaignScreen {
+    selected: usize,
+    progress: Progress,
+}
+
+impl CampaignScreen {
+    // Starts on the first level not solved yet
+    pub fn new(campaign: &Campaign) -> CampaignScreen {
+        let progress = load_progress();
+        CampaignScreen {
+            selected: progress.unlocked(campaign) - 1,
+            progress,
+        }
+    }
+
+    // Only unlocked levels can be selected
+    pub fn select(&mut self, campaign: &Campaign, step: i32) {
+        let unlocked = self.progress.unlocked(campaign) as i32;
+        self.selected = (self.selected as i32 + step).rem_euclid(unlocked) as usize;
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    fn panel(campaign: &Campaign, window_size: [f64; 2]) -> [f64; 4] {
+        let height = PANEL_PADDING * 2.0 + LINE_HEIGHT * (campaign.levels.len() + 2) as f64;
+        [
+            (window_size[0] - PANEL_WIDTH) / 2.0,
+            (window_size[1] - height) / 2.0,
+            PANEL_WIDTH,
+            height,
+        ]
+    }
+
+    fn row_y(panel: [f64; 4], row: usize) -> f64 {
+        panel[1] + PANEL_PADDING + LINE_HEIGHT * (row + 2) as f64
+    }
+
+    // Selects the unlocked level clicked on, returning whether there was one
+    pub fn click(&mut self, campaign: &Campaign, window_size: [f64; 2], pos: [f64; 2]) -> bool {
+        let panel = CampaignScreen::panel(campaign, window_size);
+        if pos[0] < panel[0] || pos[0] > panel[0] + panel[2] {
+            return false;
+        }
+        let row = (0..self.progress.unlocked(campaign)).find(|row| {
+            let y = CampaignScreen::row_y(panel, *row);
+            pos[1] >= y - ROW_PADDING && pos[1] < y - ROW_PADDING + LINE_HEIGHT
+        });
+        match row {
+            Some(row) => {
+                self.selected = row;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn render(
+        &self,
+        campaign: &Campaign,
+        window_size: [f64; 2],
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+    ) {
+        let panel = CampaignScreen::panel(campaign, window_size);
+        graphics::rectangle(COLOR_PANEL_BACKGROUND, panel, c.transform, gl);
+
+        let total: u32 = self.progress.stars.values().sum();
+        let title = format!("Campaign  {}/{} *", total, campaign.levels.len() * 3);
+        draw_text(
+            &title,
+            panel[0] + (PANEL_WIDTH - text_width(&title, TEXT_SCALE)) / 2.0,
+            panel[1] + PANEL_PADDING,
+            TEXT_SCALE,
+            COLOR_TITLE,
+            c,
+            gl,
+        );
+
+        let unlocked = self.progress.unlocked(campaign);
+        for (row, level) in campaign.levels.iter().enumerate() {
+            let y = CampaignScreen::row_y(panel, row);
+            let color = if row == self.selected {
+                let highlight = [panel[0], y - ROW_PADDING, PANEL_WIDTH, LINE_HEIGHT];
+                graphics::rectangle(COLOR_SELECTED_BACKGROUND, highlight, c.transform, gl);
+                COLOR_SELECTED
+            } else if row < unlocked {
+                COLOR_ITEM
+            } else {
+                COLOR_LOCKED
+            };
+
+            let name = format!(
+                "{:>2} {:<20} {}x{} {}",
+                row + 1,
+                level.name,
+                level.discs,
+                level.rods,
+                variant_name(level.variant)
+            );
+            draw_text(&name, panel[0] + PANEL_PADDING, y, TEXT_SCALE, color, c, gl);
+
+            let (stars, color) = match self.progress.stars.get(&level.name) {
+                Some(stars) => (star_text(*stars), COLOR_STARS),
+                None if row < unlocked => (String::from("---"), COLOR_ITEM),
+                None => (String::from("locked"), COLOR_LOCKED),
+            };
+            draw_text(
+                &stars,
+                panel[0] + PANEL_WIDTH - PANEL_PADDING - text_width(&stars, TEXT_SCALE),
+                y,
+                TEXT_SCALE,
+                color,
+                c,
+                gl,
+            );
+        }
+    }
+}
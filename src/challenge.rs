@@ -0,0 +1,115 @@
+// Challenge modes: solve within a move budget or a time limit, or play a
+// course of puzzles with growing disc counts against par, like golf
+
+use crate::solver::TowerPlan;
+
+// Extra moves a move budget allows over the optimal solution, in percent
+pub const MOVE_SLACK_PERCENT: u64 = 10;
+// Time allowed for every move of the optimal solution
+pub const SECONDS_PER_MOVE: f64 = 1.5;
+// Par allows this many percent more moves than the optimal solution,
+// so good holes can still be played under par
+pub const PAR_SLACK_PERCENT: u64 = 25;
+
+// Disc counts of the holes of a par course
+pub const PAR_COURSE_DISCS: [u32; 5] = [3, 4, 5, 6, 7];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Budget {
+    Moves(u64),
+    Seconds(f64),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Playing,
+    Won,
+    Failed,
+}
+
+// Length of the shortest solution, saturating for towers too big to count
+pub fn optimal_moves(n_discs: u32, n_rods: usize) -> u64 {
+    TowerPlan::new(n_discs, n_rods)
+        .total()
+        .map_or(u64::MAX, |total| total.min(u64::MAX as u128) as u64)
+}
+
+// `percent` more than `moves`, rounded up
+pub fn with_slack(moves: u64, percent: u64) -> u64 {
+    moves.saturating_add((moves.saturating_mul(percent)).div_ceil(100))
+}
+
+// Optimal + 10%, rounded up
+pub fn move_budget(n_discs: u32, n_rods: usize) -> Budget {
+    Budget::Moves(with_slack(
+        optimal_moves(n_discs, n_rods),
+        MOVE_SLACK_PERCENT,
+    ))
+}
+
+pub fn time_budget(n_discs: u32, n_rods: usize) -> Budget {
+    Budget::Seconds(optimal_moves(n_discs, n_rods) as f64 * SECONDS_PER_MOVE)
+}
+
+pub fn par(n_discs: u32, n_rods: usize) -> u64 {
+    with_slack(optimal_moves(n_discs, n_rods), PAR_SLACK_PERCENT)
+}
+
+// Solving wins unless the budget ran out first; running out fails straight away
+pub fn outcome(budget: Budget, moves: u64, seconds: f64, solved: bool) -> Outcome {
+    let over = match budget {
+        Budget::Moves(limit) => moves > limit,
+        Budget::Seconds(limit) => seconds > limit,
+    };
+    if over {
+        Outcome::Failed
+    } else if solved {
+        Outcome::Won
+    } else {
+        Outcome::Playing
+    }
+}
+
+// Score against par like golf: "E" for even, "+3" over, "-2" under
+pub fn format_score(score: i64) -> String {
+    match score {
+        0 => String::from("E"),
+        score if score > 0 => format!("+{}", score),
+        score => format!("{}", score),
+    }
+}
+
+pub struct ParCourse {
+    pub n_rods: usize,
+    // Moves taken on every hole played so far
+    pub cards: Vec<u64>,
+}
+
+impl ParCourse {
+    pub fn new(n_rods: usize) -> ParCourse {
+        ParCourse {
+            n_rods,
+            cards: vec![],
+        }
+    }
+
+    // Discs of the hole being played, None once the course is over
+    pub fn hole(&self) -> Option<u32> {
+        PAR_COURSE_DISCS.get(self.cards.len()).copied()
+    }
+
+    pub fn finish_hole(&mut self, moves: u64) {
+        if self.hole().is_some() {
+            self.cards.push(moves);
+        }
+    }
+
+    // Moves over par of the holes played so far
+    pub fn score(&self) -> i64 {
+        self.cards
+            .iter()
+            .zip(PAR_COURSE_DISCS)
+            .map(|(moves, discs)| *moves as i64 - par(discs, self.n_rods) as i64)
+            .sum()
+    }
+}
@@ -0,0 +1,375 @@
+use crate::campaign_screen::{load_progress, save_progress};
+use crate::settings::{config_path, read_config, write_config};
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use hanoi::achievements::star_text;
+use hanoi::campaign::Level;
+use hanoi::challenge::{
+    format_score, move_budget, optimal_moves, outcome, par, time_budget, Budget, Outcome,
+    ParCourse, PAR_COURSE_DISCS,
+};
+use hanoi::game::{Game, Variant};
+use hanoi::speedrun::format_time;
+use hanoi::stats::{Entry, Mode, Stats};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// Challenges played on the board, their HUD panel, and the stats file every
+// result is added to
+
+const STATS_FILE: &str = "stats.toml";
+
+const COLOR_PANEL_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const COLOR_TITLE: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const COLOR_COUNTER: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const COLOR_WON: [f32; 4] = [0.3, 0.9, 0.4, 1.0];
+const COLOR_FAILED: [f32; 4] = [1.0, 0.35, 0.3, 1.0];
+const COLOR_CURRENT: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_PENDING: [f32; 4] = [0.45, 0.45, 0.45, 1.0];
+
+const TEXT_SCALE: f64 = 2.0;
+const COUNTER_SCALE: f64 = 5.0;
+const LINE_HEIGHT: f64 = GLYPH_HEIGHT as f64 * TEXT_SCALE + 6.0;
+const COUNTER_HEIGHT: f64 = GLYPH_HEIGHT as f64 * COUNTER_SCALE + 12.0;
+const PANEL_PADDING: f64 = 10.0;
+
+pub enum Kind {
+    MoveLimit,
+    TimeLimit,
+    Par(ParCourse),
+    // A campaign level, by its position in the campaign
+    Level(usize, Level),
+}
+
+pub struct Challenge {
+    pub kind: Kind,
+    n_discs: u32,
+    n_rods: usize,
+    started: Instant,
+    // Start of the current par hole
+    hole_started: Instant,
+    budget: Option<Budget>,
+    // Moves of a shortest solution, for the stats
+    optimal: u64,
+    outcome: Outcome,
+    // Seconds taken, once won or failed
+    ended: Option<f64>,
+    // Won and played before this challenge, for the panel
+    record: (usize, usize),
+}
+
+// A broken stats file is reported and treated as empty
+pub fn load_stats() -> Stats {
+    read_config(STATS_FILE, Stats::from_toml)
+}
+
+fn mode(kind: &Kind) -> Mode {
+    match kind {
+        Kind::MoveLimit => Mode::MoveLimit,
+        Kind::TimeLimit => Mode::TimeLimit,
+        Kind::Par(_) => Mode::ParCourse,
+        Kind::Level(..) => Mode::Campaign,
+    }
+}
+
+// What makes a variant different, shown while playing it
+fn variant_rule(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Classic => "",
+        Variant::Cyclic => "MOVE ONE ROD RIGHT ONLY",
+        Variant::Adjacent => "MOVE TO A NEXT ROD ONLY",
+        Variant::Magnetic => "SAME POLES PUSH APART",
+    }
+}
+
+impl Challenge {
+    fn new(kind: Kind, n_discs: u32, n_rods: usize, budget: Option<Budget>) -> Challenge {
+        let stats = load_stats();
+        let mode = mode(&kind);
+        let optimal = match &kind {
+            // Levels too big to search count the moves for three stars instead
+            Kind::Level(_, level) => level.game().moves_to_solve().unwrap_or(level.stars[0]),
+            _ => optimal_moves(n_discs, n_rods),
+        };
+        Challenge {
+            kind,
+            n_discs,
+            n_rods,
+            started: Instant::now(),
+            hole_started: Instant::now(),
+            budget,
+            optimal,
+            outcome: Outcome::Playing,
+            ended: None,
+            record: (stats.won(mode), stats.played(mode)),
+        }
+    }
+
+    pub fn move_limit(n_discs: u32, n_rods: usize) -> Challenge {
+        let budget = move_budget(n_discs, n_rods);
+        Challenge::new(Kind::MoveLimit, n_discs, n_rods, Some(budget))
+    }
+
+    pub fn time_limit(n_discs: u32, n_rods: usize) -> Challenge {
+        let budget = time_budget(n_discs, n_rods);
+        Challenge::new(Kind::TimeLimit, n_discs, n_rods, Some(budget))
+    }
+
+    pub fn par_course(n_rods: usize) -> Challenge {
+        let course = ParCourse::new(n_rods);
+        Challenge::new(Kind::Par(course), PAR_COURSE_DISCS[0], n_rods, None)
+    }
+
+    pub fn level(index: usize, level: &Level) -> Challenge {
+        let budget = level.move_limit.map(Budget::Moves);
+        let kind = Kind::Level(index, level.clone());
+        Challenge::new(kind, level.discs, level.rods, budget)
+    }
+
+    // The campaign level being played, if any
+    pub fn campaign_level(&self) -> Option<&Level> {
+        match &self.kind {
+            Kind::Level(_, level) => Some(level),
+            _ => None,
+        }
+    }
+
+    // The same challenge from the start
+    pub fn again(&self) -> Challenge {
+        match &self.kind {
+            Kind::MoveLimit => Challenge::move_limit(self.n_discs, self.n_rods),
+            Kind::TimeLimit => Challenge::time_limit(self.n_discs, self.n_rods),
+            Kind::Par(_) => Challenge::par_course(self.n_rods),
+            Kind::Level(index, level) => Challenge::level(*index, level),
+        }
+    }
+
+    // Discs of the board the challenge is played on
+    pub fn n_discs(&self) -> u32 {
+        self.n_discs
+    }
+
+    pub fn playing(&self) -> bool {
+        self.outcome == Outcome::Playing
+    }
+
+    fn seconds(&self) -> f64 {
+        self.ended
+            .unwrap_or_else(|| self.hole_started.elapsed().as_secs_f64())
+    }
+
+    // Checks the game against the challenge after every move and frame,
+    // adding finished games to the stats. Returns the discs of the next par
+    // hole when one has to be set up.
+    pub fn update(&mut self, game: &Game) -> Option<u32> {
+        if !self.playing() {
+            return None;
+        }
+        let (n_discs, n_rods) = (self.n_discs, self.n_rods);
+        let moves = game.move_count() as u64;
+        let seconds = self.seconds();
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let entry = |mode, moves, optimal, seconds, won| Entry {
+            mode,
+            discs: n_discs,
+            rods: n_rods,
+            moves,
+            optimal,
+            seconds,
+            won,
+            finished_at,
+        };
+
+        let mut results = vec![];
+        let mut next_hole = None;
+        match (&mut self.kind, self.budget) {
+            (Kind::Par(course), _) if game.is_solved() => {
+                course.finish_hole(moves);
+                results.push(entry(Mode::Par, moves, par(n_discs, n_rods), seconds, true));
+
+                next_hole = course.hole();
+                if next_hole.is_none() {
+                    self.outcome = Outcome::Won;
+                    let course_par = PAR_COURSE_DISCS.iter().map(|d| par(*d, n_rods)).sum();
+                    let total = course.cards.iter().sum();
+                    let course_seconds = self.started.elapsed().as_secs_f64();
+                    results.push(entry(
+                        Mode::ParCourse,
+                        total,
+                        course_par,
+                        course_seconds,
+                        true,
+                    ));
+                }
+            }
+            (Kind::Par(_), _) => {}
+            (kind, budget) => {
+                self.outcome = match budget {
+                    Some(budget) => outcome(budget, moves, seconds, game.is_solved()),
+                    None if game.is_solved() => Outcome::Won,
+                    None => Outcome::Playing,
+                };
+                let won = self.outcome == Outcome::Won;
+                if self.outcome != Outcome::Playing {
+                    self.ended = Some(seconds);
+                    results.push(entry(mode(kind), moves, self.optimal, seconds, won));
+                }
+                if let (Kind::Level(_, level), true) = (kind, won) {
+                    let mut progress = load_progress();
+                    progress.complete(level, level.stars_for(moves));
+                    save_progress(&progress);
+                }
+            }
+        }
+
+        if !results.is_empty() {
+            let mut stats = load_stats();
+            for result in results {
+                stats.add(result);
+            }
+            if let Some(path) = config_path(STATS_FILE) {
+                write_config(&path, &stats.to_toml());
+            }
+        }
+        if let Some(n_discs) = next_hole {
+            self.n_discs = n_discs;
+            self.hole_started = Instant::now();
+        }
+        next_hole
+    }
+
+    pub fn render(
+        &self,
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+        game: &Game,
+        x: f64,
+        y: f64,
+        width: f64,
+    ) {
+        let moves = game.move_count() as u64;
+        let (title, counter) = match (&self.kind, self.budget) {
+            (Kind::Par(course), _) => (
+                format!(
+                    "PAR COURSE  HOLE {}",
+                    course.cards.len().min(PAR_COURSE_DISCS.len() - 1) + 1
+                ),
+                format_score(course.score()),
+            ),
+            (Kind::Level(index, level), budget) => (
+                format!("LEVEL {}  {}", index + 1, level.name),
+                match budget {
+                    Some(Budget::Moves(limit)) => format!("{}/{}", moves, limit),
+                    _ => moves.to_string(),
+                },
+            ),
+            (_, Some(Budget::Moves(limit))) => {
+                (String::from("MOVE LIMIT"), format!("{}/{}", moves, limit))
+            }
+            (_, Some(Budget::Seconds(limit))) => (
+                String::from("TIME LIMIT"),
+                format_time(limit - self.seconds()),
+            ),
+            (_, None) => return,
+        };
+        let status = match (&self.kind, self.outcome) {
+            (Kind::Par(_), Outcome::Won) => String::from("COURSE COMPLETE!"),
+            (Kind::Par(_), _) => format!("PAR {}  MOVES {}", par(self.n_discs, self.n_rods), moves),
+            (Kind::Level(_, level), Outcome::Won) => {
+                format!("SOLVED!  {}", star_text(level.stars_for(moves)))
+            }
+            (Kind::Level(_, level), Outcome::Playing) => variant_rule(level.variant).to_string(),
+            (_, Outcome::Won) => String::from("SOLVED!"),
+            (Kind::MoveLimit | Kind::Level(..), Outcome::Failed) => String::from("OUT OF MOVES"),
+            (_, Outcome::Failed) => String::from("OUT OF TIME"),
+            (_, Outcome::Playing) => String::new(),
+        };
+        let status_color = match self.outcome {
+            Outcome::Playing => COLOR_COUNTER,
+            Outcome::Won => COLOR_WON,
+            Outcome::Failed => COLOR_FAILED,
+        };
+
+        let extra_lines = match &self.kind {
+            Kind::Par(_) => PAR_COURSE_DISCS.len(),
+            Kind::Level(..) => 1,
+            _ => 0,
+        };
+        let height = PANEL_PADDING * 2.0 + COUNTER_HEIGHT + LINE_HEIGHT * (extra_lines + 2) as f64;
+        graphics::rectangle(
+            COLOR_PANEL_BACKGROUND,
+            [x, y, width, height],
+            c.transform,
+            gl,
+        );
+
+        let text_x = x + PANEL_PADDING;
+        let right = x + width - PANEL_PADDING;
+        let mut line_y = y + PANEL_PADDING;
+        let title = match &self.kind {
+            Kind::Level(..) => title,
+            _ => format!("{}  WON {}/{}", title, self.record.0, self.record.1),
+        };
+        draw_text(&title, text_x, line_y, TEXT_SCALE, COLOR_TITLE, c, gl);
+
+        line_y += LINE_HEIGHT;
+        draw_text(
+            &counter,
+            right - text_width(&counter, COUNTER_SCALE),
+            line_y,
+            COUNTER_SCALE,
+            COLOR_COUNTER,
+            c,
+            gl,
+        );
+
+        line_y += COUNTER_HEIGHT;
+        draw_text(&status, text_x, line_y, TEXT_SCALE, status_color, c, gl);
+
+        // Moves needed for every star
+        if let Kind::Level(_, level) = &self.kind {
+            line_y += LINE_HEIGHT;
+            let [three, two, one] = level.stars;
+            let stars = format!("*** {}  ** {}  * {}", three, two, one);
+            draw_text(&stars, text_x, line_y, TEXT_SCALE, COLOR_CURRENT, c, gl);
+        }
+
+        // Score card of the par course
+        if let Kind::Par(course) = &self.kind {
+            for (hole, discs) in PAR_COURSE_DISCS.iter().enumerate() {
+                line_y += LINE_HEIGHT;
+                let hole_par = par(*discs, self.n_rods);
+                let (moves, color) = match course.cards.get(hole) {
+                    Some(moves) => (
+                        format!(
+                            "{:>4} {:>4}",
+                            moves,
+                            format_score(*moves as i64 - hole_par as i64)
+                        ),
+                        COLOR_COUNTER,
+                    ),
+                    None if hole == course.cards.len() => (String::from("-"), COLOR_CURRENT),
+                    None => (String::from("-"), COLOR_PENDING),
+                };
+                draw_text(
+                    &format!("HOLE {}  {} DISCS  PAR {}", hole + 1, discs, hole_par),
+                    text_x,
+                    line_y,
+                    TEXT_SCALE,
+                    color,
+                    c,
+                    gl,
+                );
+                draw_text(
+                    &moves,
+                    right - text_width(&moves, TEXT_SCALE),
+                    line_y,
+                    TEXT_SCALE,
+                    color,
+                    c,
+                    gl,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,497 @@
+// Command line subcommands, run instead of opening the game window
+
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::accessibility::DiscLabels;
+use crate::awards::load_achievements;
+use crate::challenge_mode::load_stats;
+use crate::export::{export_gif, export_svg};
+use hanoi::achievements::ACHIEVEMENTS;
+use hanoi::bindings::Bindings;
+use hanoi::campaign::Campaign;
+use hanoi::challenge::format_score;
+use hanoi::game::{rod_name, Board, Move, MAX_RODS, MIN_RODS};
+use hanoi::snapshot::render_board;
+use hanoi::solver::TowerPlan;
+use hanoi::speedrun::{format_time, Run};
+use hanoi::stats::MODES;
+use hanoi::theme::Theme;
+
+// The solver streams towers far taller than any board
+const MAX_DISCS: u32 = 1000;
+
+const USAGE: &str = "usage:
+  hanoi [--assets DIR] [--theme NAME|FILE] [--bindings FILE]
+        [--campaign FILE] [--labels none|numbers|patterns] [--high-contrast]
+                             start the game, loading textures from DIR,
+                             colours from a built-in theme or a theme file,
+                             controls from a bindings file and levels from
+                             a campaign file
+  hanoi solve --discs N [--rods K] [--from A] [--to C]
+              [--format text|json|csv] [--count-only] [--range START..END]
+  hanoi [--theme NAME|FILE] render --state AABCA [--rods K] -o FILE.png
+  hanoi [--theme NAME|FILE] export-gif --discs N [--rods K] [--replay FILE]
+              -o FILE.gif|FILE.png [--fps N] [--move-time SECONDS]
+              [--width PIXELS] [--caption]
+  hanoi [--theme NAME|FILE] export-svg --state AABCA [--rods K] -o FILE.svg
+  hanoi [--theme NAME|FILE] export-svg --discs N [--rods K] [--replay FILE]
+              [--columns C] -o FILE.svg
+  hanoi verify-run FILE      replay a saved speedrun and print its time
+                             and splits
+  hanoi stats                print how many challenges were played and won
+  hanoi achievements         list achievements and which are unlocked
+
+  Move numbers start at 1; ranges are START..END (END excluded), START..=END,
+  START.. or ..END. A state gives the rod of every disc, smallest first.
+  Replays list moves as written by `hanoi solve`, or as rod pairs like AC AB CB;
+  export-gif writes an APNG when the output ends in .png. It takes at most 999
+  moves, and solutions too long to animate smoothly get one frame per move.
+  A bindings file maps actions to lists of inputs, like `undo = [\"Z\", \"pad:x\"]`.
+  Inputs are piston key names, mouse:Left, pad:a or pad:9, or hat:left. Actions
+  are select_left, select_right, select_up, select_down, pick_place, cancel,
+  undo, redo, hint, solve, restart, pause, quit, settings, fullscreen,
+  next_theme, cycle_labels, high_contrast, state_graph, binary_overlay,
+  export_svg, speedrun, move_challenge, time_challenge, par_course and campaign.
+  A campaign file lists [[levels]] with a name, discs, rods, and optionally a
+  variant (classic, cyclic, adjacent or magnetic), a start like \"AAB\", a
+  target rod, a move_limit and stars = [three, two, one] most moves.
+  The game's settings are kept in torri-di-hanoi/settings.toml in the user's
+  config directory, and --theme overrides the theme saved there. Speedrun
+  records go in records.toml, every finished run in replays/, challenge
+  results in stats.toml, campaign progress in progress.toml and achievements
+  in achievements.toml next to it. Solved puzzles get 3 stars for the fewest
+  moves, 2 for up to 25% more and 1 otherwise; puzzles helped by hints or the
+  solver are rated but unlock no achievements";
+
+#[derive(Copy, Clone, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+struct SolveOptions {
+    n_discs: u32,
+    n_rods: usize,
+    from: usize,
+    to: Option<usize>,
+    format: Format,
+    count_only: bool,
+    // Move numbers, from 1
+    range: Range<u128>,
+}
+
+struct RenderOptions {
+    board: Board,
+    output: PathBuf,
+}
+
+// Runs the subcommand in `args`, returning the process exit code
+pub fn run(args: &[String], theme: &Theme) -> i32 {
+    let result = match args[0].as_str() {
+        "solve" => parse_solve(&args[1..]).and_then(|options| {
+            write_solution(&options, &mut BufWriter::new(io::stdout().lock()))
+                .or_else(ignore_broken_pipe)
+                .map_err(|err| err.to_string())
+        }),
+        "render" => parse_render(&args[1..]).and_then(|options| {
+            render_board(&options.board, theme)
+                .save(&options.output)
+                .map_err(|err| format!("could not write {}: {}", options.output.display(), err))
+        }),
+        "export-gif" => export_gif(&args[1..], theme),
+        "export-svg" => export_svg(&args[1..], theme),
+        "verify-run" => verify_run(&args[1..]),
+        "stats" => print_stats(&args[1..]),
+        "achievements" => print_achievements(&args[1..]),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return 0;
+        }
+        other => Err(format!("unknown command '{}'", other)),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(message) => fail(&message),
+    }
+}
+
+// Checks a speedrun replay move by move, so a record can be trusted
+fn verify_run(args: &[String]) -> Result<(), String> {
+    let [path] = args else {
+        return Err(String::from("verify-run takes one replay file"));
+    };
+    let text =
+        std::fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path, err))?;
+    let run = Run::from_toml(&text).map_err(|err| format!("{}: {}", path, err))?;
+    let time = run
+        .verify()
+        .map_err(|err| format!("{}: invalid run: {}", path, err))?;
+
+    println!(
+        "valid run: {} discs on {} rods in {} moves, {}",
+        run.n_discs,
+        run.n_rods,
+        run.moves.len(),
+        format_time(time)
+    );
+    for (disc, split) in run.splits().iter().enumerate() {
+        println!(
+            "disc {:>2}  {}",
+            disc + 1,
+            split.map(format_time).unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+// Sums up the stats file, one line per challenge mode
+fn print_stats(args: &[String]) -> Result<(), String> {
+    if !args.is_empty() {
+        return Err(String::from("stats takes no arguments"));
+    }
+    let stats = load_stats();
+    for (name, mode) in MODES {
+        let best = match stats.best(mode) {
+            Some(best) => format!(", best {}", format_score(best)),
+            None => String::new(),
+        };
+        println!(
+            "{:<11} {} won of {}{}",
+            name,
+            stats.won(mode),
+            stats.played(mode),
+            best
+        );
+    }
+    Ok(())
+}
+
+// One line per achievement, unlocked ones marked with a star
+fn print_achievements(args: &[String]) -> Result<(), String> {
+    if !args.is_empty() {
+        return Err(String::from("achievements takes no arguments"));
+    }
+    let achievements = load_achievements();
+    for achievement in ACHIEVEMENTS {
+        let mark = if achievements.has(achievement) {
+            '*'
+        } else {
+            ' '
+        };
+        println!(
+            "{} {:<18} {}",
+            mark,
+            achievement.title(),
+            achievement.description()
+        );
+    }
+    Ok(())
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("hanoi: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+// Removes `name VALUE` from the arguments, returning the value
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let i = args.iter().position(|arg| arg == name)?;
+    if i + 1 >= args.len() {
+        fail(&format!("missing value for {}", name));
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Some(value)
+}
+
+// Removes `--assets DIR` from the arguments, returning the folder
+pub fn take_assets_option(args: &mut Vec<String>) -> Option<PathBuf> {
+    take_option(args, "--assets").map(PathBuf::from)
+}
+
+// Removes `name` from the arguments, returning whether it was there
+pub fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let len = args.len();
+    args.retain(|arg| arg != name);
+    args.len() != len
+}
+
+// Removes `--labels none|numbers|patterns` from the arguments
+pub fn take_labels_option(args: &mut Vec<String>) -> DiscLabels {
+    match take_option(args, "--labels") {
+        Some(value) => DiscLabels::parse(&value)
+            .unwrap_or_else(|| fail(&format!("unknown disc labels '{}'", value))),
+        None => DiscLabels::None,
+    }
+}
+
+// Removes `--bindings FILE` from the arguments, reporting every problem in the file
+pub fn take_bindings_option(args: &mut Vec<String>) -> Bindings {
+    match take_option(args, "--bindings") {
+        Some(path) => Bindings::load(Path::new(&path)).unwrap_or_else(|problems| {
+            fail(&format!(
+                "bad bindings in {}:\n  {}",
+                path,
+                problems.join("\n  ")
+            ))
+        }),
+        None => Bindings::default(),
+    }
+}
+
+// Removes `--campaign FILE` from the arguments, reporting every problem in the file
+pub fn take_campaign_option(args: &mut Vec<String>) -> Campaign {
+    match take_option(args, "--campaign") {
+        Some(path) => Campaign::load(Path::new(&path)).unwrap_or_else(|problems| {
+            fail(&format!(
+                "bad campaign in {}:\n  {}",
+                path,
+                problems.join("\n  ")
+            ))
+        }),
+        None => Campaign::builtin(),
+    }
+}
+
+// Removes `--theme NAME|FILE` from the arguments, returning the loaded theme
+pub fn take_theme_option(args: &mut Vec<String>) -> Option<Theme> {
+    take_option(args, "--theme")
+        .map(|name| Theme::load(&name).unwrap_or_else(|err| fail(&err.to_string())))
+}
+
+// Output piped into `head` and similar is not an error
+fn ignore_broken_pipe(err: io::Error) -> io::Result<()> {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+pub fn parse_rod(value: &str) -> Result<usize, String> {
+    let mut chars = value.chars();
+    let rod = match (chars.next(), chars.next()) {
+        (Some(c), None) => hanoi::game::parse_rod(c.to_ascii_uppercase()),
+        _ => None,
+    };
+    rod.ok_or_else(|| format!("'{}' is not a rod letter", value))
+}
+
+pub fn parse_number<T: std::str::FromStr>(value: &str, what: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid {}", value, what))
+}
+
+fn parse_range(value: &str) -> Result<Range<u128>, String> {
+    let (start, end) = value
+        .split_once("..")
+        .ok_or_else(|| format!("'{}' is not a range", value))?;
+
+    let start = if start.is_empty() {
+        1
+    } else {
+        parse_number(start, "move number")?
+    };
+    let end = if let Some(end) = end.strip_prefix('=') {
+        parse_number::<u128>(end, "move number")?.saturating_add(1)
+    } else if end.is_empty() {
+        u128::MAX
+    } else {
+        parse_number(end, "move number")?
+    };
+
+    if start == 0 {
+        return Err(String::from("move numbers start at 1"));
+    }
+    if start >= end {
+        return Err(format!("the range '{}' has no moves in it", value));
+    }
+    Ok(start..end)
+}
+
+fn parse_solve(args: &[String]) -> Result<SolveOptions, String> {
+    let mut n_discs: Option<u32> = None;
+    let mut options = SolveOptions {
+        n_discs: 0,
+        n_rods: 3,
+        from: 0,
+        to: None,
+        format: Format::Text,
+        count_only: false,
+        range: 1..u128::MAX,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--count-only" {
+            options.count_only = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--discs" => n_discs = Some(parse_number(value, "disc count")?),
+            "--rods" => options.n_rods = parse_number(value, "rod count")?,
+            "--from" => options.from = parse_rod(value)?,
+            "--to" => options.to = Some(parse_rod(value)?),
+            "--format" => {
+                options.format = match value.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    _ => return Err(format!("unknown format '{}'", value)),
+                }
+            }
+            "--range" => options.range = parse_range(value)?,
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    options.n_discs = n_discs.ok_or("--discs is required")?;
+    if options.n_discs == 0 || options.n_discs > MAX_DISCS {
+        return Err(format!("the disc count must be from 1 to {}", MAX_DISCS));
+    }
+    if !(MIN_RODS..=MAX_RODS).contains(&options.n_rods) {
+        return Err(format!(
+            "the rod count must be from {} to {}",
+            MIN_RODS, MAX_RODS
+        ));
+    }
+
+    let to = options.to.unwrap_or(options.n_rods - 1);
+    if options.from >= options.n_rods || to >= options.n_rods {
+        return Err(format!(
+            "rods go from A to {}",
+            rod_name(options.n_rods - 1)
+        ));
+    }
+    if options.from == to {
+        return Err(String::from("--from and --to must be different rods"));
+    }
+    options.to = Some(to);
+
+    Ok(options)
+}
+
+// Board from a state like `AABCA`, giving the rod of every disc, smallest first.
+// Without a rod count, there are enough rods for every disc and at least three.
+pub fn parse_board(state: &str, n_rods: Option<usize>) -> Result<Board, String> {
+    let state = state
+        .chars()
+        .map(|c| parse_rod(&c.to_string()))
+        .collect::<Result<Vec<usize>, String>>()?;
+    if state.is_empty() || state.len() > MAX_DISCS as usize {
+        return Err(format!("the disc count must be from 1 to {}", MAX_DISCS));
+    }
+
+    let used = state.iter().max().unwrap() + 1;
+    let n_rods = n_rods.unwrap_or(used.max(3));
+    if !(MIN_RODS..=MAX_RODS).contains(&n_rods) {
+        return Err(format!(
+            "the rod count must be from {} to {}",
+            MIN_RODS, MAX_RODS
+        ));
+    }
+    if used > n_rods {
+        return Err(format!("rods go from A to {}", rod_name(n_rods - 1)));
+    }
+
+    Ok(Board::from_state(&state, n_rods))
+}
+
+fn parse_render(args: &[String]) -> Result<RenderOptions, String> {
+    let mut state: Option<String> = None;
+    let mut n_rods: Option<usize> = None;
+    let mut output: Option<PathBuf> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--state" => state = Some(value.clone()),
+            "--rods" => n_rods = Some(parse_number(value, "rod count")?),
+            "-o" | "--output" => output = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let state = state.ok_or("--state is required")?;
+    let output = output.ok_or("-o is required")?;
+
+    Ok(RenderOptions {
+        board: parse_board(&state, n_rods)?,
+        output,
+    })
+}
+
+fn write_move(out: &mut impl Write, format: Format, number: u128, mv: &Move) -> io::Result<()> {
+    let (disc, from, to) = (mv.disc + 1, rod_name(mv.from), rod_name(mv.to));
+    match format {
+        Format::Text => writeln!(out, "{}: disc {} {} -> {}", number, disc, from, to),
+        Format::Csv => writeln!(out, "{},{},{},{}", number, disc, from, to),
+        Format::Json => write!(
+            out,
+            "{{\"move\":{},\"disc\":{},\"from\":\"{}\",\"to\":\"{}\"}}",
+            number, disc, from, to
+        ),
+    }
+}
+
+fn write_solution(options: &SolveOptions, out: &mut impl Write) -> io::Result<()> {
+    let plan = TowerPlan::new(options.n_discs, options.n_rods);
+    let total = match plan.total() {
+        Some(total) => total.to_string(),
+        None => String::from("more than 2^128"),
+    };
+
+    if options.count_only {
+        match options.format {
+            Format::Text => writeln!(out, "{}", total)?,
+            Format::Csv => writeln!(out, "total\n{}", total)?,
+            Format::Json => writeln!(out, "{{\"total\":\"{}\"}}", total)?,
+        }
+        return out.flush();
+    }
+
+    match options.format {
+        Format::Text => {}
+        Format::Csv => writeln!(out, "move,disc,from,to")?,
+        Format::Json => write!(
+            out,
+            "{{\"discs\":{},\"rods\":{},\"total\":\"{}\",\"moves\":[",
+            options.n_discs, options.n_rods, total
+        )?,
+    }
+
+    let range = options.range.start - 1..options.range.end.saturating_sub(1);
+    let mut first = true;
+    plan.stream(
+        options.from,
+        options.to.unwrap(),
+        range,
+        &mut |index, mv| {
+            if options.format == Format::Json && !first {
+                write!(out, ",")?;
+            }
+            first = false;
+            write_move(out, options.format, index + 1, &mv)
+        },
+    )?;
+
+    match options.format {
+        Format::Text => writeln!(out, "Total: {} moves", total)?,
+        Format::Csv => {}
+        Format::Json => writeln!(out, "]}}")?,
+    }
+    out.flush()
+}
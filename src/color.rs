@@ -0,0 +1,65 @@
+// Types
+pub type RGBColor = [u8; 3];
+pub type HSVColor = [u8; 3];
+
+pub fn hsv_to_rgb(hsv: HSVColor) -> RGBColor {
+    let mut h = hsv[0] as f64 / 255.0;
+    let s = hsv[1] as f64 / 255.0;
+    let v = hsv[2] as f64 / 255.0;
+
+    let mut r: f64;
+    let mut g: f64;
+    let mut b: f64;
+
+    if s > 0.0 {
+        if h == 1.0 {
+            h = 0.0;
+        }
+
+        let i = (h * 6.0) as u32;
+        let f = h * 6.0 - i as f64;
+
+        let w = v * (1.0 - s);
+        let q = v * (1.0 - s * f);
+        let t = v * (1.0 - s * (1.0 - f));
+
+        (r, g, b) = match i {
+            0 => (v, t, w),
+            1 => (q, v, w),
+            2 => (w, v, t),
+            3 => (w, q, v),
+            4 => (t, w, v),
+            5 => (v, w, q),
+            _ => (v, v, v),
+        }
+    } else {
+        (r, g, b) = (v, v, v);
+    }
+
+    r *= 255.0;
+    g *= 255.0;
+    b *= 255.0;
+
+    let rgb: RGBColor = [r as u8, g as u8, b as u8];
+    rgb
+}
+
+// Evenly spaced hue for a disc
+pub fn compute_disc_hsv(disc: u32, n_discs: u32, saturation: u8, value: u8) -> HSVColor {
+    [((255 / n_discs) * disc) as u8, saturation, value]
+}
+
+// Colour as used by the graphics backend
+pub fn rgb_to_gl(color: RGBColor) -> [f32; 4] {
+    [
+        color[0] as f32 / 255.0,
+        color[1] as f32 / 255.0,
+        color[2] as f32 / 255.0,
+        1.0,
+    ]
+}
+
+// Product of two colours, as when tinting a texture
+pub fn multiply(a: RGBColor, b: RGBColor) -> RGBColor {
+    [0, 1, 2].map(|i| ((a[i] as u32 * b[i] as u32) / 255) as u8)
+}
@@ -0,0 +1,426 @@
+// Exports of a board or a solution: animations drawn with the CPU renderer, and SVG diagrams
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{self, FilterType};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::cli::{parse_board, parse_number, parse_rod};
+use hanoi::game::{rod_name, Game, Move, MAX_RODS, MIN_RODS};
+use hanoi::layout::{rod_center, stacked_y, BOARD_HEIGHT, BOARD_WIDTH, DISC_HEIGHT, ROD_TOP};
+use hanoi::snapshot::{draw_text, render_frame};
+use hanoi::solver::TowerPlan;
+use hanoi::svg::{board_svg, solution_svg};
+use hanoi::theme::Theme;
+
+// Longer animations would take minutes to encode and make files far too big for slides
+const MAX_FRAMES: u32 = 1000;
+// Every move needs a frame of its own, after the first one
+const MAX_MOVES: usize = MAX_FRAMES as usize - 1;
+const MAX_WIDTH: u32 = 3840;
+
+// Moving discs are lifted clear of the rods
+const LIFT_Y: f64 = ROD_TOP - DISC_HEIGHT - 20.0;
+
+const HOLD_START_MS: u32 = 1000;
+const HOLD_END_MS: u32 = 2000;
+// Speed of the GIF colour quantiser, from 1 (best) to 30 (fastest)
+const GIF_SPEED: i32 = 10;
+
+const CAPTION_SCALE: u32 = 4;
+const CAPTION_COLOR: [u8; 3] = [255, 255, 255];
+
+struct ExportOptions {
+    n_discs: u32,
+    n_rods: usize,
+    replay: Option<PathBuf>,
+    output: PathBuf,
+    fps: u32,
+    move_time: f64,
+    width: u32,
+    caption: bool,
+}
+
+// One image of the animation, shown for `delay_ms`
+struct FrameSpec {
+    floating: Option<(usize, [f64; 2])>,
+    caption: String,
+    delay_ms: u32,
+}
+
+fn parse_export(args: &[String]) -> Result<ExportOptions, String> {
+    let mut n_discs: Option<u32> = None;
+    let mut options = ExportOptions {
+        n_discs: 0,
+        n_rods: 3,
+        replay: None,
+        output: PathBuf::new(),
+        fps: 25,
+        move_time: 0.6,
+        width: 640,
+        caption: false,
+    };
+    let mut output: Option<PathBuf> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--caption" {
+            options.caption = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--discs" => n_discs = Some(parse_number(value, "disc count")?),
+            "--rods" => options.n_rods = parse_number(value, "rod count")?,
+            "--replay" => options.replay = Some(PathBuf::from(value)),
+            "-o" | "--output" => output = Some(PathBuf::from(value)),
+            "--fps" => options.fps = parse_number(value, "frame rate")?,
+            "--move-time" => options.move_time = parse_number(value, "duration")?,
+            "--width" => options.width = parse_number(value, "width")?,
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    options.n_discs = n_discs.ok_or("--discs is required")?;
+    options.output = output.ok_or("-o is required")?;
+    if options.n_discs == 0 {
+        return Err(String::from("there must be at least one disc"));
+    }
+    if !(MIN_RODS..=MAX_RODS).contains(&options.n_rods) {
+        return Err(format!(
+            "the rod count must be from {} to {}",
+            MIN_RODS, MAX_RODS
+        ));
+    }
+    if !(1..=100).contains(&options.fps) {
+        return Err(String::from("the frame rate must be from 1 to 100"));
+    }
+    if !(options.move_time > 0.0 && options.move_time <= 10.0) {
+        return Err(String::from(
+            "the move time must be more than 0 and at most 10 seconds",
+        ));
+    }
+    if !(16..=MAX_WIDTH).contains(&options.width) {
+        return Err(format!("the width must be from 16 to {}", MAX_WIDTH));
+    }
+    Ok(options)
+}
+
+fn optimal_moves(n_discs: u32, n_rods: usize) -> Result<Vec<Move>, String> {
+    let too_long = || format!("the solution is longer than {} moves", MAX_MOVES);
+    let total = TowerPlan::new(n_discs, n_rods)
+        .total()
+        .ok_or_else(too_long)?;
+    if total > MAX_MOVES as u128 {
+        return Err(too_long());
+    }
+
+    let mut moves: Vec<Move> = vec![];
+    TowerPlan::new(n_discs, n_rods)
+        .stream(0, n_rods - 1, 0..total, &mut |_, mv| {
+            moves.push(mv);
+            Ok::<(), ()>(())
+        })
+        .unwrap();
+    Ok(moves)
+}
+
+// Reads a move list, as written by `hanoi solve` in text or CSV format,
+// or as pairs of rod letters like `AC AB CB`
+fn read_replay(path: &Path) -> Result<Vec<(usize, usize)>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+
+    let mut moves = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let rods: Vec<usize> = line
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| word.chars().all(|c| c.is_ascii_uppercase()))
+            .flat_map(|word| word.chars())
+            .map(|c| parse_rod(&c.to_string()).unwrap())
+            .collect();
+
+        // Headers and totals have no rod letters
+        match rods.len() {
+            0 => {}
+            n if n % 2 == 0 => moves.extend(rods.chunks(2).map(|pair| (pair[0], pair[1]))),
+            _ => return Err(format!("{}:{}: not a list of moves", path.display(), i + 1)),
+        }
+    }
+    Ok(moves)
+}
+
+// Centre and top of a disc `t` of the way along its path from one rod to another
+fn tween(from: [f64; 2], to: [f64; 2], t: f64) -> [f64; 2] {
+    // Up, across and down, at the same speed throughout
+    let up = from[1] - LIFT_Y;
+    let across = (to[0] - from[0]).abs();
+    let down = to[1] - LIFT_Y;
+    let distance = t * (up + across + down);
+
+    if distance < up {
+        [from[0], from[1] - distance]
+    } else if distance < up + across {
+        let s = (distance - up) / across;
+        [from[0] + (to[0] - from[0]) * s, LIFT_Y]
+    } else {
+        [to[0], LIFT_Y + (distance - up - across)]
+    }
+}
+
+fn move_caption(number: usize, total: usize, mv: &Move) -> String {
+    format!(
+        "MOVE {}/{}  DISC {}  {}>{}",
+        number,
+        total,
+        mv.disc + 1,
+        rod_name(mv.from),
+        rod_name(mv.to)
+    )
+}
+
+// Plays the moves, calling `frame` for every image of the animation
+fn for_each_frame(
+    options: &ExportOptions,
+    moves: &[Move],
+    mut frame: impl FnMut(&Game, FrameSpec) -> Result<(), String>,
+) -> Result<(), String> {
+    let frames_per_move = frames_per_move(options, moves.len());
+    let frame_ms = (options.move_time * 1000.0 / frames_per_move as f64).round() as u32;
+    let n_rods = options.n_rods;
+
+    let mut game = Game::new(options.n_discs, n_rods);
+    frame(
+        &game,
+        FrameSpec {
+            floating: None,
+            caption: String::from("START"),
+            delay_ms: HOLD_START_MS,
+        },
+    )?;
+
+    for (i, mv) in moves.iter().enumerate() {
+        let (from, to) = (mv.from, mv.to);
+        let caption = move_caption(i + 1, moves.len(), mv);
+
+        let start = [
+            rod_center(from, n_rods),
+            stacked_y(game.board().rods()[from].len() as u32 - 1),
+        ];
+        let end = [
+            rod_center(to, n_rods),
+            stacked_y(game.board().rods()[to].len() as u32),
+        ];
+
+        for f in 1..frames_per_move {
+            let t = f as f64 / frames_per_move as f64;
+            frame(
+                &game,
+                FrameSpec {
+                    floating: Some((from, tween(start, end, t))),
+                    caption: caption.clone(),
+                    delay_ms: frame_ms,
+                },
+            )?;
+        }
+
+        game.try_move(from, to).unwrap();
+        let last = i + 1 == moves.len();
+        frame(
+            &game,
+            FrameSpec {
+                floating: None,
+                caption: if last && game.is_solved() {
+                    format!("SOLVED IN {} MOVES", moves.len())
+                } else {
+                    caption
+                },
+                delay_ms: if last { HOLD_END_MS } else { frame_ms },
+            },
+        )?;
+    }
+    Ok(())
+}
+
+// Size of the exported images, keeping the board's aspect ratio
+fn output_size(options: &ExportOptions) -> (u32, u32) {
+    let height = options.width as u64 * BOARD_HEIGHT as u64 / BOARD_WIDTH as u64;
+    (options.width, (height as u32).max(1))
+}
+
+// Solutions too long to tween within `MAX_FRAMES` get a single frame per move
+fn frames_per_move(options: &ExportOptions, n_moves: usize) -> u32 {
+    let tweened = ((options.fps as f64 * options.move_time).round() as u32).max(1);
+    if 1 + n_moves as u64 * tweened as u64 > MAX_FRAMES as u64 {
+        1
+    } else {
+        tweened
+    }
+}
+
+fn draw(game: &Game, spec: &FrameSpec, theme: &Theme, options: &ExportOptions) -> RgbaImage {
+    let mut image = render_frame(game.board(), theme, spec.floating);
+    if options.caption {
+        draw_text(
+            &mut image,
+            &spec.caption,
+            20,
+            20,
+            CAPTION_SCALE,
+            CAPTION_COLOR,
+        );
+    }
+
+    let (width, height) = output_size(options);
+    if width == BOARD_WIDTH {
+        image
+    } else {
+        imageops::resize(&image, width, height, FilterType::Triangle)
+    }
+}
+
+fn is_png(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png") || ext.eq_ignore_ascii_case("apng"))
+}
+
+fn write_gif(
+    options: &ExportOptions,
+    moves: &[Move],
+    theme: &Theme,
+    out: impl Write,
+) -> Result<(), String> {
+    let mut encoder = GifEncoder::new_with_speed(out, GIF_SPEED);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|err| err.to_string())?;
+
+    for_each_frame(options, moves, |game, spec| {
+        let image = draw(game, &spec, theme, options);
+        let delay = Delay::from_numer_denom_ms(spec.delay_ms, 1);
+        encoder
+            .encode_frame(Frame::from_parts(image, 0, 0, delay))
+            .map_err(|err| err.to_string())
+    })
+}
+
+fn write_apng(
+    options: &ExportOptions,
+    moves: &[Move],
+    theme: &Theme,
+    out: impl Write,
+) -> Result<(), String> {
+    let (width, height) = output_size(options);
+    let n_frames = 1 + moves.len() as u32 * frames_per_move(options, moves.len());
+
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(n_frames, 0)
+        .map_err(|err| err.to_string())?;
+    let mut writer = encoder.write_header().map_err(|err| err.to_string())?;
+
+    for_each_frame(options, moves, |game, spec| {
+        let image = draw(game, &spec, theme, options);
+        writer
+            .set_frame_delay(spec.delay_ms as u16, 1000)
+            .and_then(|_| writer.write_image_data(&image))
+            .map_err(|err| err.to_string())
+    })?;
+    writer.finish().map_err(|err| err.to_string())
+}
+
+// The optimal solution, or the moves of a replay file, checked to be legal
+fn load_moves(n_discs: u32, n_rods: usize, replay: Option<&Path>) -> Result<Vec<Move>, String> {
+    let moves = match replay {
+        Some(path) => read_replay(path)?,
+        None => return optimal_moves(n_discs, n_rods),
+    };
+    if moves.len() > MAX_MOVES {
+        return Err(format!("the replay is longer than {} moves", MAX_MOVES));
+    }
+
+    let mut game = Game::new(n_discs, n_rods);
+    moves
+        .iter()
+        .enumerate()
+        .map(|(i, &(from, to))| {
+            game.try_move(from, to)
+                .map_err(|err| format!("move {} is illegal: {}", i + 1, err))
+        })
+        .collect()
+}
+
+fn create_output(path: &Path) -> Result<BufWriter<File>, String> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|err| format!("could not write {}: {}", path.display(), err))
+}
+
+// `hanoi export-gif`: writes a GIF, or an APNG if the output ends in .png or .apng
+pub fn export_gif(args: &[String], theme: &Theme) -> Result<(), String> {
+    let options = parse_export(args)?;
+    let moves = load_moves(options.n_discs, options.n_rods, options.replay.as_deref())?;
+    let out = create_output(&options.output)?;
+
+    if is_png(&options.output) {
+        write_apng(&options, &moves, theme, out)
+    } else {
+        write_gif(&options, &moves, theme, out)
+    }
+}
+
+// `hanoi export-svg`: a single board with --state, or every step of a solution with --discs
+pub fn export_svg(args: &[String], theme: &Theme) -> Result<(), String> {
+    let mut state: Option<String> = None;
+    let mut n_discs: Option<u32> = None;
+    let mut n_rods: Option<usize> = None;
+    let mut replay: Option<PathBuf> = None;
+    let mut columns: usize = 4;
+    let mut output: Option<PathBuf> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--state" => state = Some(value.clone()),
+            "--discs" => n_discs = Some(parse_number(value, "disc count")?),
+            "--rods" => n_rods = Some(parse_number(value, "rod count")?),
+            "--replay" => replay = Some(PathBuf::from(value)),
+            "--columns" => columns = parse_number(value, "column count")?,
+            "-o" | "--output" => output = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    let output = output.ok_or("-o is required")?;
+
+    let svg = match (state, n_discs) {
+        (Some(state), None) => board_svg(&parse_board(&state, n_rods)?, theme),
+        (None, Some(n_discs)) => {
+            let n_rods = n_rods.unwrap_or(3);
+            if n_discs == 0 || !(MIN_RODS..=MAX_RODS).contains(&n_rods) {
+                return Err(format!(
+                    "there must be at least one disc and from {} to {} rods",
+                    MIN_RODS, MAX_RODS
+                ));
+            }
+            let moves = load_moves(n_discs, n_rods, replay.as_deref())?;
+            solution_svg(n_discs, n_rods, &moves, columns, theme)
+        }
+        _ => return Err(String::from("give either --state or --discs")),
+    };
+
+    let mut out = create_output(&output)?;
+    out.write_all(svg.as_bytes())
+        .and_then(|_| out.flush())
+        .map_err(|err| format!("could not write {}: {}", output.display(), err))
+}
@@ -0,0 +1,408 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+// Rod of every disc, smallest disc first
+pub type State = Vec<usize>;
+
+// Most discs a game is played with, keeping solver traces and disc widths
+// within reason
+pub const MAX_DISCS: u32 = 12;
+// Fewest and most rods of any game, every front-end drawing its rods side by side
+pub const MIN_RODS: usize = 3;
+pub const MAX_RODS: usize = 6;
+
+// Biggest state space searched for hints when there is no closed form
+const HINT_SEARCH_MAX_STATES: usize = 1 << 16;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub disc: u32,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    InvalidRod,
+    SameRod,
+    EmptyRod,
+    LargerOnSmaller,
+    // The variant doesn't allow moves between these rods
+    NotAllowed,
+    SamePoles,
+}
+
+// Rule variants, all on top of the usual ones
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    #[default]
+    Classic,
+    // Discs only move one rod to the right, from the last rod back to the first
+    Cyclic,
+    // Discs only move to a rod next to theirs
+    Adjacent,
+    // Every move turns a disc upside down, and a disc can only rest on one
+    // that is the same way up, so that opposite poles touch
+    Magnetic,
+}
+
+pub const VARIANTS: [Variant; 4] = [
+    Variant::Classic,
+    Variant::Cyclic,
+    Variant::Adjacent,
+    Variant::Magnetic,
+];
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            MoveError::InvalidRod => "there is no such rod",
+            MoveError::SameRod => "the disc is already on that rod",
+            MoveError::EmptyRod => "there is no disc on that rod",
+            MoveError::LargerOnSmaller => "a disc can't go on top of a smaller one",
+            MoveError::NotAllowed => "discs can't move between those rods in this variant",
+            MoveError::SamePoles => "the disc would be pushed away by the same pole",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+pub fn rod_name(rod: usize) -> char {
+    (b'A' + rod as u8) as char
+}
+
+// The rod named by an uppercase letter, the other way around from `rod_name`
+pub fn parse_rod(name: char) -> Option<usize> {
+    name.is_ascii_uppercase()
+        .then(|| (name as u8 - b'A') as usize)
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Board {
+    // Disc values on every rod, bottom first; the smallest disc is 0
+    rods: Vec<Vec<u32>>,
+    variant: Variant,
+    // Magnetic discs that are upside down, by disc
+    flipped: Vec<bool>,
+}
+
+impl Board {
+    // All discs stacked on the first rod
+    pub fn new(n_discs: u32, n_rods: usize) -> Board {
+        Board::from_state(&vec![0; n_discs as usize], n_rods)
+    }
+
+    pub fn from_state(state: &[usize], n_rods: usize) -> Board {
+        Board::with_variant(state, n_rods, Variant::Classic)
+    }
+
+    // Magnetic discs all start the same way up
+    pub fn with_variant(state: &[usize], n_rods: usize, variant: Variant) -> Board {
+        let mut rods: Vec<Vec<u32>> = vec![vec![]; n_rods];
+        for (disc, rod) in state.iter().enumerate().rev() {
+            rods[*rod].push(disc as u32);
+        }
+        Board {
+            rods,
+            variant,
+            flipped: vec![false; state.len()],
+        }
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn flipped(&self, disc: u32) -> bool {
+        self.flipped[disc as usize]
+    }
+
+    pub fn rods(&self) -> &[Vec<u32>] {
+        &self.rods
+    }
+
+    pub fn n_rods(&self) -> usize {
+        self.rods.len()
+    }
+
+    pub fn n_discs(&self) -> u32 {
+        self.rods.iter().map(|rod| rod.len() as u32).sum()
+    }
+
+    pub fn top(&self, rod: usize) -> Option<u32> {
+        self.rods.get(rod).and_then(|rod| rod.last().copied())
+    }
+
+    pub fn check_move(&self, from: usize, to: usize) -> Result<Move, MoveError> {
+        if from >= self.n_rods() || to >= self.n_rods() {
+            return Err(MoveError::InvalidRod);
+        }
+        if from == to {
+            return Err(MoveError::SameRod);
+        }
+        let allowed = match self.variant {
+            Variant::Classic | Variant::Magnetic => true,
+            Variant::Cyclic => to == (from + 1) % self.n_rods(),
+            Variant::Adjacent => from.abs_diff(to) == 1,
+        };
+        if !allowed {
+            return Err(MoveError::NotAllowed);
+        }
+        let disc = self.top(from).ok_or(MoveError::EmptyRod)?;
+        if self.top(to).is_some_and(|top| top < disc) {
+            return Err(MoveError::LargerOnSmaller);
+        }
+        // The disc turns over on the way
+        if self.variant == Variant::Magnetic
+            && self
+                .top(to)
+                .is_some_and(|top| self.flipped(top) == self.flipped(disc))
+        {
+            return Err(MoveError::SamePoles);
+        }
+        Ok(Move { disc, from, to })
+    }
+
+    pub fn can_move(&self, from: usize, to: usize) -> bool {
+        self.check_move(from, to).is_ok()
+    }
+
+    fn apply(&mut self, mv: &Move) {
+        let disc = self.rods[mv.from].pop();
+        debug_assert_eq!(disc, Some(mv.disc));
+        self.rods[mv.to].push(mv.disc);
+        if self.variant == Variant::Magnetic {
+            self.flipped[mv.disc as usize] = !self.flipped[mv.disc as usize];
+        }
+    }
+
+    pub fn state(&self) -> State {
+        let mut state: State = vec![0; self.n_discs() as usize];
+        for (i_rod, rod) in self.rods.iter().enumerate() {
+            for disc in rod.iter() {
+                state[*disc as usize] = i_rod;
+            }
+        }
+        state
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves: Vec<Move> = vec![];
+        for from in 0..self.n_rods() {
+            for to in 0..self.n_rods() {
+                if let Ok(mv) = self.check_move(from, to) {
+                    moves.push(mv);
+                }
+            }
+        }
+        moves
+    }
+}
+
+// A game in progress: the board plus everything needed to undo, redo and give hints
+pub struct Game {
+    board: Board,
+    start: Board,
+    target: usize,
+    moves: Vec<Move>,
+    undone: Vec<Move>,
+    // Moves taken back since the start
+    undos: u32,
+}
+
+impl Game {
+    pub fn new(n_discs: u32, n_rods: usize) -> Game {
+        Game::from_board(Board::new(n_discs, n_rods), n_rods - 1)
+    }
+
+    // A game starting from any position, solved when every disc is on `target`
+    pub fn from_board(board: Board, target: usize) -> Game {
+        Game {
+            start: board.clone(),
+            board,
+            target,
+            moves: vec![],
+            undone: vec![],
+            undos: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // Board the game started from
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn undo_count(&self) -> u32 {
+        self.undos
+    }
+
+    pub fn try_move(&mut self, from: usize, to: usize) -> Result<Move, MoveError> {
+        let mv = self.board.check_move(from, to)?;
+        self.board.apply(&mv);
+        self.moves.push(mv);
+        self.undone.clear();
+        Ok(mv)
+    }
+
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.moves.pop()?;
+        self.board.apply(&Move {
+            disc: mv.disc,
+            from: mv.to,
+            to: mv.from,
+        });
+        self.undone.push(mv);
+        self.undos += 1;
+        Some(mv)
+    }
+
+    pub fn redo(&mut self) -> Option<Move> {
+        let mv = self.undone.pop()?;
+        self.board.apply(&mv);
+        self.moves.push(mv);
+        Some(mv)
+    }
+
+    pub fn restart(&mut self) {
+        self.board = self.start.clone();
+        self.moves.clear();
+        self.undone.clear();
+        self.undos = 0;
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.board.rods[self.target].len() as u32 == self.board.n_discs()
+    }
+
+    // Every state the board has been in, starting position first
+    pub fn states(&self) -> Vec<State> {
+        let mut board = self.start.clone();
+        let mut states: Vec<State> = vec![board.state()];
+        for mv in self.moves.iter() {
+            board.apply(mv);
+            states.push(board.state());
+        }
+        states
+    }
+
+    // Next move of a shortest solution from the current position
+    pub fn hint(&self) -> Option<Move> {
+        if self.is_solved() {
+            return None;
+        }
+        if self.board.n_rods() == 3 && self.board.variant() == Variant::Classic {
+            return Some(hint_three_rods(&self.board, self.target));
+        }
+        search(&self.board, self.target).map(|(mv, _)| mv)
+    }
+
+    // Length of a shortest solution from the current position, None when it
+    // is too long to search for
+    pub fn moves_to_solve(&self) -> Option<u64> {
+        if self.is_solved() {
+            return Some(0);
+        }
+        if self.board.n_rods() == 3 && self.board.variant() == Variant::Classic {
+            return Some(moves_three_rods(&self.board, self.target));
+        }
+        search(&self.board, self.target).map(|(_, moves)| moves)
+    }
+
+    // Length of a shortest solution from the starting position
+    pub fn optimal_moves(&self) -> Option<u64> {
+        Game::from_board(self.start.clone(), self.target).moves_to_solve()
+    }
+}
+
+// With three rods, the biggest misplaced disc has to go straight to its target,
+// so everything smaller has to be moved onto the remaining rod first
+fn hint_three_rods(board: &Board, target: usize) -> Move {
+    let state = board.state();
+    let mut target = target;
+    let mut next: Option<Move> = None;
+
+    for disc in (0..state.len()).rev() {
+        if state[disc] != target {
+            next = Some(Move {
+                disc: disc as u32,
+                from: state[disc],
+                to: target,
+            });
+            target = 3 - state[disc] - target;
+        }
+    }
+
+    next.unwrap()
+}
+
+// Same idea: every misplaced disc, biggest first, takes 2^disc moves
+fn moves_three_rods(board: &Board, target: usize) -> u64 {
+    let state = board.state();
+    let mut target = target;
+    let mut moves = 0;
+
+    for disc in (0..state.len()).rev() {
+        if state[disc] != target {
+            moves += 1u64 << disc;
+            target = 3 - state[disc] - target;
+        }
+    }
+
+    moves
+}
+
+// Breadth first search over boards, for more than three rods and for
+// variants. Returns the first move and length of a shortest solution.
+fn search(board: &Board, target: usize) -> Option<(Move, u64)> {
+    let n_rods = board.n_rods();
+    let mut n_states = (n_rods as u64).checked_pow(board.n_discs())?;
+    if board.variant() == Variant::Magnetic {
+        n_states = n_states.checked_mul(1u64.checked_shl(board.n_discs())?)?;
+    }
+    if n_states > HINT_SEARCH_MAX_STATES as u64 {
+        return None;
+    }
+
+    let n_discs = board.n_discs() as usize;
+
+    // First move taken to reach every board, and how many moves it took
+    let mut first: HashMap<Board, (Move, u64)> = HashMap::new();
+    let mut queue: VecDeque<Board> = VecDeque::new();
+    queue.push_back(board.clone());
+
+    while let Some(current) = queue.pop_front() {
+        if current.rods[target].len() == n_discs {
+            return first.get(&current).copied();
+        }
+        let reached = first.get(&current).copied();
+        for mv in current.legal_moves() {
+            let mut next = current.clone();
+            next.apply(&mv);
+            if next != *board && !first.contains_key(&next) {
+                let (first_move, moves) = reached.unwrap_or((mv, 0));
+                first.insert(next.clone(), (first_move, moves + 1));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
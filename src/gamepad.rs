@@ -0,0 +1,115 @@
+use piston::input::Event;
+
+// Gamepads, read with gilrs because the glutin window doesn't report them, and
+// turned into the piston controller events the bindings are made of. Without
+// the `gamepad` feature there are simply none.
+
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+#[cfg(feature = "gamepad")]
+mod gilrs_events {
+    use gilrs::{Axis, Button as PadButton, EventType};
+    use hanoi::bindings::{
+        PAD_A, PAD_B, PAD_BACK, PAD_DPAD_DOWN, PAD_DPAD_LEFT, PAD_DPAD_RIGHT, PAD_DPAD_UP,
+        PAD_LEFT_SHOULDER, PAD_RIGHT_SHOULDER, PAD_START, PAD_Y, STICK_AXIS,
+    };
+    use piston::input::{
+        Button, ButtonArgs, ButtonState, ControllerAxisArgs, ControllerButton, Event, Input, Motion,
+    };
+
+    // Button numbers of the SDL game controller layout used by the bindings
+    fn pad_button(button: PadButton) -> Option<u8> {
+        Some(match button {
+            PadButton::South => PAD_A,
+            PadButton::East => PAD_B,
+            PadButton::West => 2,
+            PadButton::North => PAD_Y,
+            PadButton::Select => PAD_BACK,
+            PadButton::Mode => 5,
+            PadButton::Start => PAD_START,
+            PadButton::LeftThumb => 7,
+            PadButton::RightThumb => 8,
+            PadButton::LeftTrigger => PAD_LEFT_SHOULDER,
+            PadButton::RightTrigger => PAD_RIGHT_SHOULDER,
+            PadButton::DPadUp => PAD_DPAD_UP,
+            PadButton::DPadDown => PAD_DPAD_DOWN,
+            PadButton::DPadLeft => PAD_DPAD_LEFT,
+            PadButton::DPadRight => PAD_DPAD_RIGHT,
+            _ => return None,
+        })
+    }
+
+    // Stick axes, pointing down like SDL's instead of up like gilrs'
+    fn pad_axis(axis: Axis, value: f32) -> Option<(u8, f64)> {
+        let value = value as f64;
+        Some(match axis {
+            Axis::LeftStickX => (STICK_AXIS, value),
+            Axis::LeftStickY => (1, -value),
+            Axis::RightStickX => (2, value),
+            Axis::RightStickY => (3, -value),
+            _ => return None,
+        })
+    }
+
+    fn button(id: u32, state: ButtonState, button: u8) -> Input {
+        Input::Button(ButtonArgs {
+            state,
+            button: Button::Controller(ControllerButton::new(id, button)),
+            scancode: None,
+        })
+    }
+
+    pub fn to_event(event: gilrs::Event) -> Option<Event> {
+        let id = usize::from(event.id) as u32;
+        let input = match event.event {
+            EventType::ButtonPressed(pressed, _) => {
+                button(id, ButtonState::Press, pad_button(pressed)?)
+            }
+            EventType::ButtonReleased(released, _) => {
+                button(id, ButtonState::Release, pad_button(released)?)
+            }
+            EventType::AxisChanged(axis, value, _) => {
+                let (axis, position) = pad_axis(axis, value)?;
+                Input::Move(Motion::ControllerAxis(ControllerAxisArgs::new(
+                    id, axis, position,
+                )))
+            }
+            _ => return None,
+        };
+        Some(Event::Input(input, None))
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepads {
+    // Gamepads that can't be read are reported once and left out
+    pub fn new() -> Gamepads {
+        let gilrs = gilrs::Gilrs::new()
+            .map_err(|err| eprintln!("gamepads are not available: {}", err))
+            .ok();
+        Gamepads { gilrs }
+    }
+
+    // Everything the gamepads did since the last poll
+    pub fn poll(&mut self) -> Vec<Event> {
+        let mut events = vec![];
+        while let Some(event) = self.gilrs.as_mut().and_then(|gilrs| gilrs.next_event()) {
+            events.extend(gilrs_events::to_event(event));
+        }
+        events
+    }
+}
+
+#[cfg(not(feature = "gamepad"))]
+impl Gamepads {
+    pub fn new() -> Gamepads {
+        Gamepads {}
+    }
+
+    pub fn poll(&mut self) -> Vec<Event> {
+        vec![]
+    }
+}
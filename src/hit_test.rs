@@ -0,0 +1,159 @@
+// The two coordinate spaces of the game window, and what lies under a point.
+// Window points are pixels from the window's top left corner; board points are
+// from the play area's top left corner, as used by `layout`.
+
+use crate::game::Board;
+use crate::layout::{disc_rect, disc_width, rod_dropbox, BOARD_HEIGHT, BOARD_WIDTH, DISC_HEIGHT};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WindowPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoardPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+// Where the board's top left corner is drawn in the window
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Viewport {
+    // The board centred in a window of the given size
+    pub fn centered(window_width: f64, window_height: f64) -> Viewport {
+        Viewport {
+            x: (window_width - BOARD_WIDTH as f64) / 2.0,
+            y: (window_height - BOARD_HEIGHT as f64) / 2.0,
+        }
+    }
+
+    pub fn to_board(&self, point: WindowPoint) -> BoardPoint {
+        BoardPoint {
+            x: point.x - self.x,
+            y: point.y - self.y,
+        }
+    }
+
+    pub fn to_window(&self, point: BoardPoint) -> WindowPoint {
+        WindowPoint {
+            x: point.x + self.x,
+            y: point.y + self.y,
+        }
+    }
+
+    // A rectangle given in board coordinates, in window coordinates
+    pub fn rect_to_window(&self, [x, y, width, height]: [f64; 4]) -> [f64; 4] {
+        [x + self.x, y + self.y, width, height]
+    }
+}
+
+// How far the point is outside the rectangle, 0 if it is inside
+fn distance([x, y, width, height]: [f64; 4], point: BoardPoint) -> f64 {
+    let dx = (x - point.x).max(point.x - (x + width)).max(0.0);
+    let dy = (y - point.y).max(point.y - (y + height)).max(0.0);
+    dx.hypot(dy)
+}
+
+// Rod whose top disc is under the point; only top discs can be picked up
+pub fn disc_at(board: &Board, point: BoardPoint) -> Option<usize> {
+    disc_near(board, point, 0.0)
+}
+
+// Rod whose top disc is closest to the point, if it is at most `margin` away.
+// Fingers are less precise than a mouse, so touches get a margin around discs.
+pub fn disc_near(board: &Board, point: BoardPoint, margin: f64) -> Option<usize> {
+    let (n_discs, n_rods) = (board.n_discs(), board.n_rods());
+    board
+        .rods()
+        .iter()
+        .enumerate()
+        .filter_map(|(rod, discs)| {
+            let disc = *discs.last()?;
+            let rect = disc_rect(disc, n_discs, rod, n_rods, discs.len() as u32 - 1);
+            Some((rod, distance(rect, point)))
+        })
+        .filter(|(_, distance)| *distance <= margin)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(rod, _)| rod)
+}
+
+// Rod whose column of the board contains the point, where a disc can be dropped
+pub fn rod_at(n_rods: usize, point: BoardPoint) -> Option<usize> {
+    (0..n_rods).find(|rod| {
+        let (start, end) = rod_dropbox(*rod, n_rods);
+        point.x >= start && point.x < end
+    })
+}
+
+// Top centre of a disc of the given width moved as little as possible to lie on the board
+pub fn clamp_disc(top_center: BoardPoint, width: f64) -> BoardPoint {
+    let half = width / 2.0;
+    BoardPoint {
+        x: top_center
+            .x
+            .clamp(half, (BOARD_WIDTH as f64 - half).max(half)),
+        y: top_center
+            .y
+            .clamp(0.0, (BOARD_HEIGHT as f64 - DISC_HEIGHT).max(0.0)),
+    }
+}
+
+// A disc being dragged, remembering where on the disc it was grabbed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Drag {
+    pub from: usize,
+    pub disc: u32,
+    // From the pointer to the disc's top centre
+    grab_x: f64,
+    grab_y: f64,
+}
+
+impl Drag {
+    // Picks up the top disc under the pointer, if there is one
+    pub fn start(board: &Board, pointer: BoardPoint) -> Option<Drag> {
+        Drag::start_near(board, pointer, 0.0)
+    }
+
+    // Picks up the closest top disc at most `margin` away from the pointer
+    pub fn start_near(board: &Board, pointer: BoardPoint, margin: f64) -> Option<Drag> {
+        let from = disc_near(board, pointer, margin)?;
+        let discs = &board.rods()[from];
+        let disc = *discs.last().unwrap();
+        let rect = disc_rect(
+            disc,
+            board.n_discs(),
+            from,
+            board.n_rods(),
+            discs.len() as u32 - 1,
+        );
+
+        Some(Drag {
+            from,
+            disc,
+            grab_x: rect[0] + rect[2] / 2.0 - pointer.x,
+            grab_y: rect[1] - pointer.y,
+        })
+    }
+
+    // Top centre of the dragged disc, kept on the board
+    pub fn disc_position(&self, n_discs: u32, pointer: BoardPoint) -> BoardPoint {
+        clamp_disc(
+            BoardPoint {
+                x: pointer.x + self.grab_x,
+                y: pointer.y + self.grab_y,
+            },
+            disc_width(self.disc, n_discs),
+        )
+    }
+
+    // Rod the disc would be dropped on
+    pub fn target(&self, n_rods: usize, pointer: BoardPoint) -> Option<usize> {
+        rod_at(n_rods, pointer)
+    }
+}
@@ -0,0 +1,64 @@
+// Where rods and discs sit on the board, in board coordinates (origin at the
+// top left of the play area), shared by the window and the PNG renderer
+
+pub const BOARD_WIDTH: u32 = 1280;
+pub const BOARD_HEIGHT: u32 = 720;
+pub const DISC_WIDTH_MIN: f64 = 100.0;
+pub const DISC_WIDTH_MAX: f64 = 350.0;
+pub const DISC_HEIGHT: f64 = 60.0;
+pub const ROD_WIDTH: f64 = 20.0;
+pub const ROD_HEIGHT: f64 = 500.0;
+pub const ROD_BASE: f64 = BOARD_HEIGHT as f64 - 100.0;
+pub const ROD_TOP: f64 = ROD_BASE - ROD_HEIGHT;
+// Tallest stack whose top disc is still on the board, where it can be picked up
+pub const MAX_BOARD_DISCS: u32 = (ROD_BASE / DISC_HEIGHT) as u32;
+
+// Each rod owns an equal vertical strip of the board
+fn rod_strip(n_rods: usize) -> f64 {
+    BOARD_WIDTH as f64 / n_rods as f64
+}
+
+pub fn rod_center(rod: usize, n_rods: usize) -> f64 {
+    rod_strip(n_rods) / 2.0 + rod_strip(n_rods) * rod as f64
+}
+
+pub fn rod_rect(rod: usize, n_rods: usize) -> [f64; 4] {
+    [
+        rod_center(rod, n_rods) - ROD_WIDTH / 2.0,
+        ROD_TOP,
+        ROD_WIDTH,
+        ROD_HEIGHT,
+    ]
+}
+
+// Horizontal range where a disc can be dropped onto the rod
+pub fn rod_dropbox(rod: usize, n_rods: usize) -> (f64, f64) {
+    (
+        rod_strip(n_rods) * rod as f64,
+        rod_strip(n_rods) * (rod + 1) as f64,
+    )
+}
+
+pub fn disc_width(disc: u32, n_discs: u32) -> f64 {
+    // A lone disc is as narrow as the smallest of a stack
+    if n_discs <= 1 {
+        return DISC_WIDTH_MIN;
+    }
+    let width_step = (DISC_WIDTH_MAX - DISC_WIDTH_MIN) / (n_discs - 1) as f64;
+    DISC_WIDTH_MIN + width_step * disc as f64
+}
+
+// Top of the disc at height `level` of a stack, the bottom one being 0
+pub fn stacked_y(level: u32) -> f64 {
+    ROD_BASE - DISC_HEIGHT * (level + 1) as f64
+}
+
+pub fn disc_rect(disc: u32, n_discs: u32, rod: usize, n_rods: usize, level: u32) -> [f64; 4] {
+    let width = disc_width(disc, n_discs);
+    [
+        rod_center(rod, n_rods) - width / 2.0,
+        stacked_y(level),
+        width,
+        DISC_HEIGHT,
+    ]
+}
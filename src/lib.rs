@@ -0,0 +1,18 @@
+// Game core shared by the graphical and terminal front-ends
+pub mod achievements;
+pub mod bindings;
+pub mod campaign;
+pub mod challenge;
+pub mod color;
+pub mod font;
+pub mod game;
+pub mod hit_test;
+pub mod layout;
+pub mod snapshot;
+pub mod solver;
+pub mod speedrun;
+pub mod sprites;
+pub mod stats;
+pub mod svg;
+pub mod theme;
+pub mod touch;
@@ -2,64 +2,94 @@ extern crate glutin_window;
 extern crate graphics;
 extern crate opengl_graphics;
 extern crate piston;
+mod accessibility;
+mod assets;
+mod awards;
+mod binary_overlay;
+mod call_tree;
+mod campaign_screen;
+mod challenge_mode;
+mod cli;
+mod export;
+mod gamepad;
+mod settings;
+mod state_graph;
+mod text;
 mod textures;
+mod timed_run;
 
+use glutin::prelude::GlSurface;
+use glutin::surface::SwapInterval;
 use glutin_window::GlutinWindow as Window;
 use opengl_graphics::{GlGraphics, OpenGL};
-use piston::event_loop::{EventSettings, Events};
+use piston::event_loop::{EventLoop, EventSettings, Events};
 use piston::input::{
-    Button, MouseButton, MouseCursorEvent, PressEvent, ReleaseEvent, RenderArgs, RenderEvent,
-    UpdateArgs, UpdateEvent,
+    Button, ControllerAxisArgs, ControllerAxisEvent, Event, MouseCursorEvent, PressEvent,
+    ReleaseEvent, RenderArgs, RenderEvent, Touch, TouchArgs, TouchEvent, UpdateArgs, UpdateEvent,
 };
 use piston::window::WindowSettings;
-// use piston::Window;
+use piston::{AdvancedWindow as _, Window as _};
+use winit::window::Fullscreen;
 
-use textures::{
-    compute_disc_color, load_disc_texture_color, load_rod_texture, DiscTexture, RodTexture,
+use accessibility::{
+    render_label, DiscLabels, COLOR_HC_BACKGROUND, COLOR_HC_HIGHLIGHT, COLOR_HC_INVALID,
+    COLOR_HC_OUTLINE, COLOR_HC_ROD,
 };
+use assets::Assets;
+use awards::Awards;
+use call_tree::AutoSolve;
+use campaign_screen::CampaignScreen;
+use challenge_mode::{Challenge, Kind};
+use gamepad::Gamepads;
+use hanoi::bindings::{Action, Binding, Bindings, STICK_AXIS, STICK_PUSH, STICK_RELEASE};
+use hanoi::campaign::Campaign;
+use hanoi::color::{multiply, rgb_to_gl, RGBColor};
+use hanoi::game::{Board, Game, Move, State, Variant};
+use hanoi::hit_test::{disc_at, BoardPoint, Drag, Viewport, WindowPoint};
+use hanoi::layout::{
+    disc_rect, disc_width, rod_center, rod_rect, stacked_y, BOARD_HEIGHT, BOARD_WIDTH, DISC_HEIGHT,
+    ROD_BASE, ROD_HEIGHT, ROD_TOP, ROD_WIDTH,
+};
+use hanoi::solver::Trace;
+use hanoi::svg::board_svg;
+use hanoi::theme::Theme;
+use hanoi::touch::{TouchAction, TouchInput, TouchPhase};
+use settings::{InputMode, Settings, SettingsScreen, WindowMode};
+use state_graph::{StateGraph, GRAPH_MAX_DISCS, GRAPH_RODS};
+use std::ffi::{c_char, CStr};
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use text::{draw_text, text_width, GLYPH_HEIGHT};
+use textures::{DiscTexture, RodTexture, TextureManager};
+use timed_run::Speedrun;
 
 // Constants
 static WINDOW_TITLE: &str = "Torri di Hanoi";
-const WINDOW_WIDTH: u32 = 1280;
-const WINDOW_HEIGHT: u32 = 720;
-const DISC_WIDTH_MIN: f64 = 100.0;
-const DISC_WIDTH_MAX: f64 = 350.0;
-const DISC_HEIGHT: f64 = 60.0;
-const ROD_WIDTH: f64 = 20.0;
-const ROD_HEIGHT: f64 = 500.0;
-const ROD_BASE: f64 = WINDOW_HEIGHT as f64 - 100.0;
-const ROD_TOP: f64 = ROD_BASE - ROD_HEIGHT;
-const N_DISCS: u32 = 5;
-const N_RODS: u32 = 3;
-
-const COLOR_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
-const COLOR_PLAY_AREA_BACKGROUND: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
-
-fn clamp_rect_position(
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
-    screen_width: f64,
-    screen_height: f64,
-) -> (f64, f64) {
-    let mut new_pos_x = x;
-    let mut new_pos_y = y;
+// Colour discs by tinting one grayscale texture on the GPU, instead of baking a texture per colour
+const GPU_TINT: bool = true;
 
-    if new_pos_x < 0.0 {
-        new_pos_x = 0.0;
-    } else if new_pos_x > screen_width - width {
-        new_pos_x = screen_width - width;
-    }
+// Drag feedback, drawn with vertex colours so it needs no shaders
+const INVALID_TINT: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+const GHOST_ALPHA: f32 = 0.35;
+const COLOR_SHADOW: [f32; 4] = [0.0, 0.0, 0.0, 0.4];
+const SHADOW_OFFSET: [f64; 2] = [10.0, 14.0];
 
-    if new_pos_y < 0.0 {
-        new_pos_y = 0.0;
-    } else if new_pos_y > screen_height - height {
-        new_pos_y = screen_height - height;
-    }
+// Fingers can pick up discs this far outside them
+const TOUCH_MARGIN: f64 = 40.0;
 
-    (new_pos_x, new_pos_y)
-}
+const COLOR_PAUSE_DIM: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const COLOR_PAUSE_TEXT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const PAUSED_TEXT: &str = "PAUSED";
+const PAUSED_SCALE: f64 = 8.0;
+
+// Which way up magnetic discs are, shown as a stripe along their top
+const COLOR_POLE_NORTH: [f32; 4] = [0.9, 0.2, 0.2, 1.0];
+const COLOR_POLE_SOUTH: [f32; 4] = [0.2, 0.4, 0.95, 1.0];
+const POLE_HEIGHT: f64 = 6.0;
+
+const STATE_GRAPH_PANEL: [f64; 4] = [10.0, 10.0, 280.0, 250.0];
+const CALL_TREE_PANEL_WIDTH: f64 = 440.0;
+const SPEEDRUN_PANEL_WIDTH: f64 = 520.0;
 
 struct Rod {
     width: f64,
@@ -67,10 +97,10 @@ struct Rod {
     pos_x: f64,
     pos_y: f64,
     center: f64,
-    highlighted: bool,
-    dropbox_start: f64,
-    dropbox_end: f64,
-    texture: RodTexture,
+    drop_target: DropTarget,
+    texture: Rc<RodTexture>,
+    tint: [f32; 4],
+    highlight_tint: [f32; 4],
 }
 
 impl Rod {
@@ -78,455 +108,1400 @@ impl Rod {
         &self,
         c: graphics::Context,
         gl: &mut opengl_graphics::GlGraphics,
-        play_area_render_info: &PlayAreaRenderInfo,
+        viewport: &Viewport,
+        high_contrast: bool,
     ) {
-        let image = graphics::Image::new().rect([
-            self.pos_x + play_area_render_info.x,
-            self.pos_y + play_area_render_info.y,
-            self.width,
-            self.height,
-        ]);
-
-        image.draw(
-            if self.highlighted {
-                &self.texture.highlight
-            } else {
-                &self.texture.normal
-            },
-            &graphics::DrawState::default(),
-            c.transform,
-            gl,
-        );
-    }
+        let rect = viewport.rect_to_window([self.pos_x, self.pos_y, self.width, self.height]);
+
+        if high_contrast {
+            let color = match self.drop_target {
+                DropTarget::None => COLOR_HC_ROD,
+                DropTarget::Valid => COLOR_HC_HIGHLIGHT,
+                DropTarget::Invalid => COLOR_HC_INVALID,
+            };
+            graphics::rectangle(color, rect, c.transform, gl);
+            return;
+        }
+
+        let (texture, tint) = match self.drop_target {
+            DropTarget::None => (&self.texture.normal, self.tint),
+            DropTarget::Valid => (&self.texture.highlight, self.highlight_tint),
+            DropTarget::Invalid => (&self.texture.normal, tint_gl(self.tint, INVALID_TINT)),
+        };
+        let image = graphics::Image::new_color(tint).rect(rect);
 
-    fn pos_in_dropbox(&self, x: f64, _y: f64, pari: &PlayAreaRenderInfo) -> bool {
-        x >= self.dropbox_start + pari.x && x <= self.dropbox_end + pari.y
+        image.draw(texture, &graphics::DrawState::default(), c.transform, gl);
     }
 }
 
 struct Disc {
-    width: f64,
     value: u32,
+    width: f64,
+    pixel_size: f64,
     highlighted: bool,
-    texture: DiscTexture,
+    texture: Rc<DiscTexture>,
+    tint: [f32; 4],
+    highlight_tint: [f32; 4],
 }
 
 impl Disc {
-    fn render(&self, gl: &mut GlGraphics, args: &RenderArgs, center_x: f64, y: f64) {
+    fn render(
+        &self,
+        gl: &mut GlGraphics,
+        args: &RenderArgs,
+        center_x: f64,
+        y: f64,
+        style: &DiscStyle,
+        look: DiscLook,
+    ) {
         // Calculate actual x based on center x and width
         let x = center_x - self.width / 2.0;
 
-        let pixel_size = DISC_HEIGHT / 12.0;
-
-        let img_left = graphics::Image::new().rect([x, y, pixel_size, DISC_HEIGHT]);
-        let img_middle = graphics::Image::new().rect([
-            x + pixel_size,
-            y,
-            self.width - pixel_size * 2.0,
-            DISC_HEIGHT,
-        ]);
-        let img_right =
-            graphics::Image::new().rect([x + self.width - pixel_size, y, pixel_size, DISC_HEIGHT]);
+        let pixel_size = self.pixel_size;
+        let rect = [x, y, self.width, DISC_HEIGHT];
+        let (texture, tint) = match look {
+            DiscLook::Normal | DiscLook::Ghost | DiscLook::Shadow => {
+                (&self.texture.normal, self.tint)
+            }
+            DiscLook::Hover | DiscLook::Lifted => (&self.texture.highlight, self.highlight_tint),
+            DiscLook::Invalid => (
+                &self.texture.highlight,
+                tint_gl(self.highlight_tint, INVALID_TINT),
+            ),
+        };
+        let tint = match look {
+            DiscLook::Ghost => [tint[0], tint[1], tint[2], GHOST_ALPHA],
+            DiscLook::Shadow => COLOR_SHADOW,
+            _ => tint,
+        };
+        let image = graphics::Image::new_color(tint).rect(rect);
 
         gl.draw(args.viewport(), |c, gl| {
-            img_left.draw(
-                if self.highlighted {
-                    &self.texture.left_highlight
-                } else {
-                    &self.texture.left
-                },
-                &graphics::DrawState::default(),
-                c.transform,
-                gl,
-            );
+            image.draw(texture, &graphics::DrawState::default(), c.transform, gl);
 
-            // Draw middle
-            img_middle.draw(
-                if self.highlighted {
-                    &self.texture.middle_highlight
-                } else {
-                    &self.texture.middle
-                },
-                &graphics::DrawState::default(),
-                c.transform,
-                gl,
-            );
+            // Previews and shadows are only the disc's shape
+            if look == DiscLook::Ghost || look == DiscLook::Shadow {
+                return;
+            }
 
-            img_right.draw(
-                if self.highlighted {
-                    &self.texture.right_highlight
-                } else {
-                    &self.texture.right
-                },
-                &graphics::DrawState::default(),
-                c.transform,
-                gl,
-            );
+            render_label(style.labels, self.value, rect, pixel_size, c, gl);
+
+            if style.high_contrast {
+                let (color, radius) = match look {
+                    DiscLook::Hover | DiscLook::Lifted => (COLOR_HC_HIGHLIGHT, pixel_size * 1.5),
+                    DiscLook::Invalid => (COLOR_HC_INVALID, pixel_size * 1.5),
+                    _ => (COLOR_HC_OUTLINE, pixel_size * 0.5),
+                };
+                graphics::Rectangle::new_border(color, radius).draw(
+                    rect,
+                    &graphics::DrawState::default(),
+                    c.transform,
+                    gl,
+                );
+            }
         })
     }
+}
 
-    fn pos_in(&self, x: f64, y: f64, pos_center_x: f64, pos_y: f64) -> bool {
-        let pos_x = pos_center_x - self.width / 2.0;
-        (x >= pos_x && x <= pos_x + self.width) && (y >= pos_y && y <= pos_y + DISC_HEIGHT)
-    }
-
-    fn clamped_pos(&self, x: f64, y: f64, pari: &PlayAreaRenderInfo) -> (f64, f64) {
-        // TODO remove unnecessary calculation from centre to absolute to centre again
-        let (clamped_x, clamped_y) = clamp_rect_position(
-            x - self.width / 2.0,
-            y,
-            self.width,
-            DISC_HEIGHT,
-            pari.width,
-            pari.height,
-        );
-        (clamped_x + self.width / 2.0 + pari.x, clamped_y + pari.y)
-    }
+// Feedback tiers, from resting to being dragged
+#[derive(Copy, Clone, PartialEq)]
+enum DiscLook {
+    Normal,
+    Hover,
+    Lifted,
+    // Dragged over a rod that can't take it
+    Invalid,
+    // Faint preview of where the disc would land
+    Ghost,
+    Shadow,
+}
 
-    fn calc_movement_offset(
-        &self,
-        x: f64,
-        y: f64,
-        pos_center_x: f64,
-        pos_y: f64,
-        pari: &PlayAreaRenderInfo,
-    ) -> (f64, f64) {
-        let offset_x: f64 = pos_center_x - x;
-        let offset_y: f64 = pos_y - y;
+// Whether the dragged disc is over a rod, and if it can go there
+#[derive(Copy, Clone, PartialEq)]
+enum DropTarget {
+    None,
+    Valid,
+    Invalid,
+}
 
-        (offset_x - pari.x, offset_y - pari.y)
-    }
+// Multiplies two tints together
+fn tint_gl(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] * b[i])
 }
 
-fn calc_stacked_y(stack: u32) -> f64 {
-    ROD_BASE - DISC_HEIGHT * (stack + 1) as f64
+// How discs are drawn on top of their texture
+#[derive(Copy, Clone)]
+struct DiscStyle {
+    labels: DiscLabels,
+    high_contrast: bool,
 }
 
-fn calc_rod_value(rod: &Vec<Disc>) -> u32 {
-    if rod.len() < 1 {
-        return u32::MAX;
-    } else {
-        return rod.last().unwrap().value;
-    }
+// States the game went through and the shortest way on from the last one,
+// worked out again only when the game changes
+#[derive(Default)]
+struct History {
+    board: Option<Board>,
+    target: usize,
+    moves: Vec<Move>,
+    states: Vec<State>,
+    optimal: Vec<State>,
 }
 
-#[derive(Copy, Clone)]
-struct PlayAreaRenderInfo {
-    width: f64,
-    height: f64,
-    x: f64,
-    y: f64,
+impl History {
+    fn update(&mut self, game: &Game, state_graph: Option<&StateGraph>) {
+        // The board and the moves that led to it pin down the whole game
+        if self.board.as_ref() == Some(game.board())
+            && self.target == game.target()
+            && self.moves == game.moves()
+        {
+            return;
+        }
+        self.board = Some(game.board().clone());
+        self.target = game.target();
+        self.moves = game.moves().to_vec();
+        self.states = game.states();
+        self.optimal = match state_graph {
+            Some(state_graph) => {
+                let goal: State = vec![game.target(); state_graph.n_discs() as usize];
+                state_graph.shortest_path(self.states.last().unwrap(), &goal)
+            }
+            None => vec![],
+        };
+    }
 }
 
 pub struct PlayArea {
     gl: GlGraphics, // OpenGL drawing backend.
 
+    theme: Theme,
+    textures: TextureManager,
+    disc_style: DiscStyle,
+
     // Elements
-    discs: Vec<Vec<Disc>>,
+    game: Game,
+    discs: Vec<Disc>,
     rods: Vec<Rod>,
 
-    // Mouse position
-    mouse_pos_x: f64,
-    mouse_pos_y: f64,
+    mouse: WindowPoint,
+    drag: Option<Drag>,
+    touch: TouchInput,
+
+    // Rod selected with the keyboard or a gamepad, hidden while using a pointer
+    cursor: Option<usize>,
+    stick_pushed: bool,
 
-    // Movement variables
-    moving_disc: Option<Disc>,
-    mov_ofst_x: f64,
-    mov_ofst_y: f64,
-    start_rod: usize,
+    // Suggested move, and the move count it was asked for at
+    hint: Option<(usize, Move)>,
+    paused: bool,
 
-    last_pari: PlayAreaRenderInfo,
+    // Where the board was last drawn
+    viewport: Viewport,
+
+    // State graph side view
+    state_graph: Option<StateGraph>,
+    show_state_graph: bool,
+    // Only kept up to date while the state graph or binary overlay is shown
+    history: History,
+
+    auto_solve: Option<AutoSolve>,
+    animation_speed: f64,
+    speedrun: Option<Speedrun>,
+    challenge: Option<Challenge>,
+    // Star ratings and achievements of puzzles solved by hand
+    awards: Awards,
+    input_mode: InputMode,
+
+    show_binary_overlay: bool,
 }
 
 impl PlayArea {
-    fn render(&mut self, args: &RenderArgs, play_area_render_info: PlayAreaRenderInfo) {
-        self.gl.draw(args.viewport(), |c, gl| {
-            // Clear the screen.
-            // graphics::clear(COLOR_PLAY_AREA_BACKGROUND, gl);
-
-            self.last_pari = play_area_render_info.clone();
+    fn render(&mut self, args: &RenderArgs, viewport: Viewport) {
+        let graph_shown = self.show_state_graph && self.state_graph.is_some();
+        if graph_shown || self.show_binary_overlay {
+            self.history.update(&self.game, self.state_graph.as_ref());
+        }
 
-            let window_size = args.window_size;
+        self.gl.draw(args.viewport(), |c, gl| {
+            self.viewport = viewport;
 
+            let background = if self.disc_style.high_contrast {
+                COLOR_HC_BACKGROUND
+            } else {
+                rgb_to_gl(self.theme.play_area_background)
+            };
             graphics::rectangle(
-                COLOR_PLAY_AREA_BACKGROUND,
-                [
-                    play_area_render_info.x,
-                    play_area_render_info.y,
-                    play_area_render_info.width,
-                    play_area_render_info.height,
-                ],
+                background,
+                viewport.rect_to_window([0.0, 0.0, BOARD_WIDTH as f64, BOARD_HEIGHT as f64]),
                 c.transform,
                 gl,
             );
 
             // Render all rods
             for rod in self.rods.iter() {
-                rod.render(c, gl, &play_area_render_info);
+                rod.render(c, gl, &viewport, self.disc_style.high_contrast);
             }
 
-            // Render all discs
-            for (i_rod, rod) in self.discs.iter().enumerate() {
-                for (i, disc) in rod.iter().enumerate() {
-                    disc.render(
+            let hint = self
+                .hint
+                .filter(|(count, _)| *count == self.game.move_count() && self.drag.is_none())
+                .map(|(_, mv)| mv);
+            let hinted = hint.map(|mv| mv.disc);
+
+            // Render all discs, except the one being moved
+            for (i_rod, rod) in self.game.board().rods().iter().enumerate() {
+                for (i, value) in rod.iter().enumerate() {
+                    if self.drag.is_some_and(|drag| drag.disc == *value) {
+                        continue;
+                    }
+                    let position = viewport.to_window(BoardPoint {
+                        x: self.rods[i_rod].center,
+                        y: stacked_y(i as u32),
+                    });
+                    self.discs[*value as usize].render(
                         gl,
                         args,
-                        self.rods[i_rod].center + play_area_render_info.x,
-                        calc_stacked_y(i as u32) + play_area_render_info.y,
+                        position.x,
+                        position.y,
+                        &self.disc_style,
+                        if self.discs[*value as usize].highlighted || hinted == Some(*value) {
+                            DiscLook::Hover
+                        } else {
+                            DiscLook::Normal
+                        },
                     );
                 }
             }
 
+            // Show where the hinted disc should go
+            if let Some(mv) = hint {
+                let position = viewport.to_window(BoardPoint {
+                    x: self.rods[mv.to].center,
+                    y: stacked_y(self.game.board().rods()[mv.to].len() as u32),
+                });
+                self.discs[mv.disc as usize].render(
+                    gl,
+                    args,
+                    position.x,
+                    position.y,
+                    &self.disc_style,
+                    DiscLook::Ghost,
+                );
+            }
+
+            if self.game.board().variant() == Variant::Magnetic {
+                let board = self.game.board();
+                for (i_rod, rod) in board.rods().iter().enumerate() {
+                    for (level, disc) in rod.iter().enumerate() {
+                        if self.drag.is_some_and(|drag| drag.disc == *disc) {
+                            continue;
+                        }
+                        let rect = viewport.rect_to_window(disc_rect(
+                            *disc,
+                            board.n_discs(),
+                            i_rod,
+                            board.n_rods(),
+                            level as u32,
+                        ));
+                        let color = if board.flipped(*disc) {
+                            COLOR_POLE_SOUTH
+                        } else {
+                            COLOR_POLE_NORTH
+                        };
+                        let stripe = [rect[0], rect[1], rect[2], POLE_HEIGHT];
+                        graphics::rectangle(color, stripe, c.transform, gl);
+                    }
+                }
+            }
+
+            // Render state graph
+            if let (Some(state_graph), true) = (&self.state_graph, self.show_state_graph) {
+                state_graph.render(
+                    c,
+                    gl,
+                    viewport.rect_to_window(STATE_GRAPH_PANEL),
+                    &self.history.states,
+                    &self.history.optimal,
+                );
+            }
+
+            // Render binary / Gray code annotation
+            if self.show_binary_overlay {
+                binary_overlay::render(
+                    c,
+                    gl,
+                    viewport.x + 10.0,
+                    viewport.y + ROD_BASE + 15.0,
+                    &self.history.states,
+                );
+            }
+
+            // Render recursion call tree
+            if let Some(auto_solve) = &self.auto_solve {
+                auto_solve.render(
+                    c,
+                    gl,
+                    viewport.x + BOARD_WIDTH as f64 - CALL_TREE_PANEL_WIDTH - 10.0,
+                    viewport.y + 10.0,
+                    CALL_TREE_PANEL_WIDTH,
+                );
+            }
+
+            // Render speedrun timer and splits
+            if let Some(speedrun) = &self.speedrun {
+                speedrun.render(
+                    c,
+                    gl,
+                    viewport.x + BOARD_WIDTH as f64 - SPEEDRUN_PANEL_WIDTH - 10.0,
+                    viewport.y + 10.0,
+                    SPEEDRUN_PANEL_WIDTH,
+                );
+            }
+
+            // Render challenge budget or par score card
+            if let Some(challenge) = &self.challenge {
+                challenge.render(
+                    c,
+                    gl,
+                    &self.game,
+                    viewport.x + BOARD_WIDTH as f64 - SPEEDRUN_PANEL_WIDTH - 10.0,
+                    viewport.y + 10.0,
+                    SPEEDRUN_PANEL_WIDTH,
+                );
+            }
+
             // Render moving disc
-            if !matches!(self.moving_disc, None) {
-                let (clamped_x, clamped_y) = self.moving_disc.as_ref().unwrap().clamped_pos(
-                    self.mouse_pos_x + self.mov_ofst_x,
-                    self.mouse_pos_y + self.mov_ofst_y,
-                    &play_area_render_info,
+            if let Some(drag) = self.drag {
+                let moving_disc = &self.discs[drag.disc as usize];
+                let position = viewport.to_window(
+                    drag.disc_position(self.game.board().n_discs(), viewport.to_board(self.mouse)),
                 );
 
-                self.moving_disc
-                    .as_ref()
-                    .unwrap()
-                    .render(gl, args, clamped_x, clamped_y)
+                // Preview where the disc would land
+                let target = self
+                    .rods
+                    .iter()
+                    .position(|rod| rod.drop_target == DropTarget::Valid);
+                if let Some(i_rod) = target.filter(|i_rod| *i_rod != drag.from) {
+                    let ghost = viewport.to_window(BoardPoint {
+                        x: self.rods[i_rod].center,
+                        y: stacked_y(self.game.board().rods()[i_rod].len() as u32),
+                    });
+                    moving_disc.render(
+                        gl,
+                        args,
+                        ghost.x,
+                        ghost.y,
+                        &self.disc_style,
+                        DiscLook::Ghost,
+                    );
+                }
+
+                let invalid = self
+                    .rods
+                    .iter()
+                    .any(|rod| rod.drop_target == DropTarget::Invalid);
+                moving_disc.render(
+                    gl,
+                    args,
+                    position.x + SHADOW_OFFSET[0],
+                    position.y + SHADOW_OFFSET[1],
+                    &self.disc_style,
+                    DiscLook::Shadow,
+                );
+                moving_disc.render(
+                    gl,
+                    args,
+                    position.x,
+                    position.y,
+                    &self.disc_style,
+                    if invalid {
+                        DiscLook::Invalid
+                    } else {
+                        DiscLook::Lifted
+                    },
+                );
+            }
+
+            if let Some(speedrun) = &self.speedrun {
+                let board = [0.0, 0.0, BOARD_WIDTH as f64, BOARD_HEIGHT as f64];
+                speedrun.render_countdown(c, gl, viewport.rect_to_window(board));
+            }
+
+            let board = [0.0, 0.0, BOARD_WIDTH as f64, BOARD_HEIGHT as f64];
+            self.awards.render(c, gl, viewport.rect_to_window(board));
+
+            if self.paused {
+                let board = [0.0, 0.0, BOARD_WIDTH as f64, BOARD_HEIGHT as f64];
+                graphics::rectangle(
+                    COLOR_PAUSE_DIM,
+                    viewport.rect_to_window(board),
+                    c.transform,
+                    gl,
+                );
+                let x =
+                    viewport.x + (BOARD_WIDTH as f64 - text_width(PAUSED_TEXT, PAUSED_SCALE)) / 2.0;
+                let y =
+                    viewport.y + (BOARD_HEIGHT as f64 - GLYPH_HEIGHT as f64 * PAUSED_SCALE) / 2.0;
+                draw_text(PAUSED_TEXT, x, y, PAUSED_SCALE, COLOR_PAUSE_TEXT, c, gl);
             }
         });
     }
 
-    fn update(&mut self, _args: &UpdateArgs) {}
+    fn update(&mut self, args: &UpdateArgs) {
+        // Time limits run out even while paused
+        self.check_challenge();
+        self.awards.update();
+        if self.paused {
+            return;
+        }
+        if let Some(auto_solve) = &mut self.auto_solve {
+            for mv in auto_solve.update(args.dt * self.animation_speed) {
+                self.game
+                    .try_move(mv.from, mv.to)
+                    .expect("solver made an illegal move");
+            }
+        }
+    }
 
-    fn mouse_moved(&mut self, pos: &[f64; 2]) {
-        // Save mouse position
-        self.mouse_pos_x = pos[0];
-        self.mouse_pos_y = pos[1];
+    // Restarts the puzzle and plays the recursive solution
+    fn start_auto_solve(&mut self) {
+        self.speedrun = None;
+        self.challenge = None;
+        self.reset_game();
+        self.awards.help();
+        for disc in self.discs.iter_mut() {
+            disc.highlighted = false;
+        }
 
-        // Higlight rods
-        if !matches!(self.moving_disc, None) {
-            for (i_rod, rod) in self.rods.iter_mut().enumerate() {
-                rod.highlighted = false;
-                if rod.pos_in_dropbox(self.mouse_pos_x, self.mouse_pos_y, &self.last_pari) {
-                    if calc_rod_value(&self.discs[i_rod])
-                        >= self.moving_disc.as_mut().unwrap().value
-                    {
-                        rod.highlighted = true;
-                    }
-                }
-            }
-        } else {
-            // Highlight discs
-            for (i_rod, rod) in self.discs.iter_mut().enumerate() {
-                if !rod.is_empty() {
-                    let last_disc_i = rod.len() - 1;
-
-                    rod[last_disc_i].highlighted = rod[last_disc_i].pos_in(
-                        self.mouse_pos_x,
-                        self.mouse_pos_y,
-                        self.rods[i_rod].center + self.last_pari.x,
-                        calc_stacked_y(last_disc_i as u32) + self.last_pari.y,
-                    )
+        self.auto_solve = Some(AutoSolve::new(Trace::new(
+            self.game.board().n_discs(),
+            0,
+            self.game.target(),
+            1,
+        )));
+    }
+
+    // Starts the puzzle over and races it against the clock
+    fn start_speedrun(&mut self) {
+        self.cancel_drag();
+        self.auto_solve = None;
+        self.challenge = None;
+        self.hint = None;
+        self.reset_game();
+        self.show_cursor();
+        self.speedrun = Some(Speedrun::new(
+            self.game.board().n_discs(),
+            self.game.board().n_rods(),
+        ));
+    }
+
+    // Starts the puzzle over as a challenge. A par course starts with the
+    // discs of its first hole.
+    fn start_challenge(&mut self, challenge: Challenge) {
+        match &challenge.kind {
+            Kind::Par(_) => self.new_game(challenge.n_discs(), self.rods.len()),
+            Kind::Level(_, level) => {
+                self.new_game(level.discs, level.rods);
+                self.game = level.game();
+                // The state graph only knows the classic rules
+                if level.variant != Variant::Classic {
+                    self.state_graph = None;
                 }
             }
+            _ => self.reset_game(),
         }
+        self.cancel_drag();
+        self.auto_solve = None;
+        self.speedrun = None;
+        self.hint = None;
+        self.show_cursor();
+        self.challenge = Some(challenge);
     }
 
-    fn mouse_button_pressed(&mut self, button: &MouseButton) {
-        if *button == MouseButton::Left {
-            // Check if mouse has been clicked inside disc
-
-            for (i_rod, rod) in self.discs.iter_mut().enumerate() {
-                if !rod.is_empty() {
-                    let last_disc = rod.last().unwrap();
-                    let last_disc_i = rod.len() - 1;
-
-                    // If mouse was clicked on a disc
-                    if last_disc.pos_in(
-                        self.mouse_pos_x,
-                        self.mouse_pos_y,
-                        self.rods[i_rod].center + self.last_pari.x,
-                        calc_stacked_y(last_disc_i as u32) + self.last_pari.y,
-                    ) {
-                        (self.mov_ofst_x, self.mov_ofst_y) = last_disc.calc_movement_offset(
-                            self.mouse_pos_x,
-                            self.mouse_pos_y,
-                            self.rods[i_rod].center + self.last_pari.x,
-                            calc_stacked_y(last_disc_i as u32) + self.last_pari.y,
-                            &self.last_pari,
-                        );
-
-                        // Remove disc and save to current disc
-                        // rod[last_disc_i].highlighted = false;
-                        self.moving_disc = Some(rod.remove(last_disc_i));
-                        self.start_rod = i_rod as usize;
-                    }
+    // Ends or fails the challenge, and sets up the next par hole
+    fn check_challenge(&mut self) {
+        let Some(challenge) = &mut self.challenge else {
+            return;
+        };
+        if let Some(n_discs) = challenge.update(&self.game) {
+            let challenge = self.challenge.take();
+            self.new_game(n_discs, self.rods.len());
+            self.challenge = challenge;
+            self.show_cursor();
+        }
+    }
+
+    // No help from undo, hints or the solver while the clock runs or a
+    // challenge is being played
+    fn racing(&self) -> bool {
+        self.speedrun
+            .as_ref()
+            .is_some_and(|speedrun| speedrun.running())
+            || self
+                .challenge
+                .as_ref()
+                .is_some_and(|challenge| challenge.playing())
+    }
+
+    // The usual puzzle from the start, in place of any campaign level
+    fn reset_game(&mut self) {
+        let n_discs = self.game.board().n_discs();
+        self.game = Game::new(n_discs, self.rods.len());
+        self.state_graph = init_state_graph(n_discs, self.rods.len());
+        self.awards.new_puzzle();
+    }
+
+    // Starts over with a different number of discs or rods
+    fn new_game(&mut self, n_discs: u32, n_rods: usize) {
+        self.cancel_drag();
+        self.auto_solve = None;
+        self.speedrun = None;
+        self.challenge = None;
+        self.hint = None;
+        self.cursor = None;
+        self.game = Game::new(n_discs, n_rods);
+        self.awards.new_puzzle();
+        self.discs = init_discs(n_discs, &self.theme, &mut self.textures);
+        self.rods = init_rods(n_rods as u32, &self.theme, &mut self.textures);
+        self.state_graph = init_state_graph(n_discs, n_rods);
+    }
+
+    // Rebuilds the discs and rods with the colours and textures of a new theme
+    fn apply_theme(&mut self, theme: Theme) {
+        self.textures.set_art(theme.art);
+        self.theme = theme;
+        let n_discs = self.game.board().n_discs();
+        let n_rods = self.game.board().n_rods() as u32;
+        self.discs = init_discs(n_discs, &self.theme, &mut self.textures);
+        self.rods = init_rods(n_rods, &self.theme, &mut self.textures);
+    }
+
+    // Saves the current board as a vector drawing in the working directory
+    fn export_svg(&self) {
+        let path = format!("hanoi-move-{}.svg", self.game.move_count());
+        match std::fs::write(&path, board_svg(self.game.board(), &self.theme)) {
+            Ok(()) => println!("saved {}", path),
+            Err(err) => eprintln!("could not write {}: {}", path, err),
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        // Only looking around is allowed while paused
+        let board_action = !matches!(
+            action,
+            Action::Pause
+                | Action::CycleLabels
+                | Action::ToggleHighContrast
+                | Action::ToggleStateGraph
+                | Action::ToggleBinaryOverlay
+                | Action::ExportSvg
+                | Action::Quit
+        );
+        if self.paused && board_action {
+            return;
+        }
+
+        match action {
+            Action::SelectLeft => self.select(-1),
+            Action::SelectRight => self.select(1),
+            Action::PickOrPlace => self.pick_or_place(),
+            Action::Cancel => {
+                self.cancel_drag();
+                self.show_cursor();
+            }
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Hint if !self.solver_playing() && !self.racing() => {
+                self.awards.help();
+                self.hint = self.game.hint().map(|mv| (self.game.move_count(), mv))
+            }
+            // Challenges start over as they were
+            Action::Restart => match self.challenge.take() {
+                Some(challenge) => self.start_challenge(challenge.again()),
+                None => {
+                    self.cancel_drag();
+                    self.auto_solve = None;
+                    self.speedrun = None;
+                    self.game.restart();
+                    self.awards.new_puzzle();
+                    self.show_cursor();
+                }
+            },
+            Action::Pause => {
+                self.cancel_drag();
+                self.paused = !self.paused;
+            }
+            Action::ToggleStateGraph => self.show_state_graph = !self.show_state_graph,
+            Action::ExportSvg => self.export_svg(),
+            Action::CycleLabels => self.disc_style.labels = self.disc_style.labels.next(),
+            Action::ToggleHighContrast => {
+                self.disc_style.high_contrast = !self.disc_style.high_contrast
+            }
+            Action::NextTheme if self.drag.is_none() => self.apply_theme(self.theme.next_builtin()),
+            Action::ToggleBinaryOverlay => self.show_binary_overlay = !self.show_binary_overlay,
+            Action::Speedrun => self.start_speedrun(),
+            Action::MoveChallenge => {
+                let board = self.game.board();
+                self.start_challenge(Challenge::move_limit(board.n_discs(), board.n_rods()))
+            }
+            Action::TimeChallenge => {
+                let board = self.game.board();
+                self.start_challenge(Challenge::time_limit(board.n_discs(), board.n_rods()))
+            }
+            Action::ParCourse => self.start_challenge(Challenge::par_course(self.rods.len())),
+            Action::Solve if self.drag.is_none() && !self.racing() => {
+                if self.auto_solve.is_some() {
+                    self.auto_solve = None;
+                } else {
+                    self.start_auto_solve();
                 }
             }
+            _ => {}
         }
     }
-    fn mouse_button_released(&mut self, button: &MouseButton) {
-        if *button == MouseButton::Left {
-            if !matches!(self.moving_disc, None) {
-                let mut currently_moving: Disc = std::mem::take(&mut self.moving_disc).unwrap();
 
-                let mut drop: Option<usize> = None;
+    // Left stick pushes select rods like the D-pad, once per push
+    fn stick_moved(&mut self, args: &ControllerAxisArgs) {
+        if args.axis != STICK_AXIS {
+            return;
+        }
+        if args.position.abs() < STICK_RELEASE {
+            self.stick_pushed = false;
+        } else if args.position.abs() > STICK_PUSH && !self.stick_pushed {
+            self.stick_pushed = true;
+            self.select(args.position.signum() as i32);
+        }
+    }
 
-                for (i_rod, rod) in self.rods.iter_mut().enumerate() {
-                    if rod.pos_in_dropbox(self.mouse_pos_x, self.mouse_pos_y, &self.last_pari)
-                        && calc_rod_value(&self.discs[i_rod]) >= currently_moving.value
-                    {
-                        drop = Some(i_rod);
-                        break;
-                    }
-                }
+    // Where a disc held with the keyboard or a gamepad floats, over its rod
+    fn lift_point(&self, rod: usize) -> WindowPoint {
+        self.viewport.to_window(BoardPoint {
+            x: self.rods[rod].center,
+            y: ROD_TOP - DISC_HEIGHT,
+        })
+    }
+
+    // Moves the rod selection by `step` rods, wrapping around
+    fn select(&mut self, step: i32) {
+        let n_rods = self.rods.len() as i32;
+        let rod = match self.cursor {
+            Some(rod) => (rod as i32 + step).rem_euclid(n_rods) as usize,
+            None => self.drag.map_or(0, |drag| drag.from),
+        };
+        self.cursor = Some(rod);
+
+        if self.drag.is_some() {
+            self.pointer_moved(self.lift_point(rod));
+        } else {
+            self.show_cursor();
+        }
+    }
+
+    fn pick_or_place(&mut self) {
+        let Some(rod) = self.cursor else {
+            self.select(0);
+            return;
+        };
+
+        if self.drag.is_some() {
+            self.pointer_moved(self.lift_point(rod));
+            self.pointer_released();
+            self.show_cursor();
+        } else if let Some(disc) = self.game.board().top(rod) {
+            // Grab the disc by its middle, then lift it
+            let level = self.game.board().rods()[rod].len() as u32 - 1;
+            let rect = disc_rect(
+                disc,
+                self.game.board().n_discs(),
+                rod,
+                self.rods.len(),
+                level,
+            );
+            self.mouse = self.viewport.to_window(BoardPoint {
+                x: rect[0] + rect[2] / 2.0,
+                y: rect[1] + rect[3] / 2.0,
+            });
+            self.pointer_pressed(0.0);
+            self.pointer_moved(self.lift_point(rod));
+        }
+    }
+
+    // Highlights the top disc of the selected rod, or the rod if it is empty
+    fn show_cursor(&mut self) {
+        let Some(rod) = self.cursor else {
+            return;
+        };
+        let top = self.game.board().top(rod);
+        for disc in self.discs.iter_mut() {
+            disc.highlighted = top == Some(disc.value);
+        }
+        for (i_rod, r) in self.rods.iter_mut().enumerate() {
+            r.drop_target = if i_rod == rod && top.is_none() {
+                DropTarget::Valid
+            } else {
+                DropTarget::None
+            };
+        }
+    }
 
-                currently_moving.highlighted = false;
+    fn pointer_moved(&mut self, position: WindowPoint) {
+        self.mouse = position;
+        let pointer = self.viewport.to_board(self.mouse);
 
-                if matches!(drop, None) {
-                    self.discs[self.start_rod].push(currently_moving);
+        // Highlight the rod under a dragged disc, or the disc under the pointer
+        if let Some(drag) = self.drag {
+            let target = drag.target(self.rods.len(), pointer);
+            for (i_rod, rod) in self.rods.iter_mut().enumerate() {
+                rod.drop_target = if target != Some(i_rod) {
+                    DropTarget::None
+                } else if i_rod == drag.from || self.game.board().can_move(drag.from, i_rod) {
+                    DropTarget::Valid
                 } else {
-                    self.discs[drop.unwrap()].push(currently_moving);
-                }
+                    DropTarget::Invalid
+                };
+            }
+        } else {
+            let hovered =
+                disc_at(self.game.board(), pointer).and_then(|i_rod| self.game.board().top(i_rod));
+            for disc in self.discs.iter_mut() {
+                disc.highlighted = hovered == Some(disc.value);
+            }
+        }
+    }
 
-                self.moving_disc = None;
+    // The board belongs to the solver while it plays
+    fn solver_playing(&mut self) -> bool {
+        match &self.auto_solve {
+            Some(auto_solve) if !auto_solve.finished() => true,
+            Some(_) => {
+                self.auto_solve = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    // Lifts the top disc at most `margin` away from the pointer off its rod
+    fn pointer_pressed(&mut self, margin: f64) {
+        if self.paused || self.solver_playing() {
+            return;
+        }
+        if self
+            .speedrun
+            .as_ref()
+            .is_some_and(|speedrun| speedrun.counting_down())
+        {
+            return;
+        }
+        // Failed challenges lock the board until the next game
+        if self
+            .challenge
+            .as_ref()
+            .is_some_and(|challenge| !challenge.playing())
+        {
+            return;
+        }
+        self.drag = Drag::start_near(
+            self.game.board(),
+            self.viewport.to_board(self.mouse),
+            margin,
+        );
+    }
 
-                for rod in self.rods.iter_mut() {
-                    rod.highlighted = false;
+    // A pointer button goes down: in click-to-place mode the second click
+    // puts the disc down
+    fn pointer_down(&mut self, margin: f64) {
+        if self.input_mode == InputMode::Click && self.drag.is_some() {
+            self.pointer_released();
+        } else {
+            self.pointer_pressed(margin);
+        }
+    }
+
+    fn pointer_up(&mut self) {
+        if self.input_mode == InputMode::Drag {
+            self.pointer_released();
+        }
+    }
+
+    fn pointer_released(&mut self) {
+        if let Some(drag) = self.drag {
+            let pointer = self.viewport.to_board(self.mouse);
+
+            // Illegal drops just put the disc back where it was
+            let played = drag
+                .target(self.rods.len(), pointer)
+                .and_then(|i_rod| self.game.try_move(drag.from, i_rod).ok());
+            if let (Some(mv), Some(speedrun)) = (played, &mut self.speedrun) {
+                speedrun.record(mv);
+                if self.game.is_solved() {
+                    speedrun.finish();
                 }
             }
+            if played.is_some() {
+                self.awards.moved(
+                    &self.game,
+                    self.challenge.as_ref().and_then(Challenge::campaign_level),
+                );
+                self.check_challenge();
+            }
+        }
+        self.cancel_drag();
+    }
+
+    fn cancel_drag(&mut self) {
+        if let Some(drag) = self.drag.take() {
+            self.discs[drag.disc as usize].highlighted = false;
+            for rod in self.rods.iter_mut() {
+                rod.drop_target = DropTarget::None;
+            }
+        }
+    }
+
+    fn undo(&mut self) {
+        if !self.paused && !self.solver_playing() && !self.racing() {
+            self.cancel_drag();
+            self.game.undo();
+            self.show_cursor();
+        }
+    }
+
+    fn redo(&mut self) {
+        if !self.paused && !self.solver_playing() && !self.racing() {
+            self.cancel_drag();
+            if self.game.redo().is_some() {
+                self.awards.moved(
+                    &self.game,
+                    self.challenge.as_ref().and_then(Challenge::campaign_level),
+                );
+            }
+            self.show_cursor();
         }
     }
-}
 
-fn place_play_area(window_width: f64, window_height: f64) -> PlayAreaRenderInfo {
-    PlayAreaRenderInfo {
-        width: WINDOW_WIDTH as f64,
-        height: WINDOW_HEIGHT as f64,
-        x: window_width / 2.0 - WINDOW_WIDTH as f64 / 2.0,
-        y: window_height / 2.0 - WINDOW_HEIGHT as f64 / 2.0,
+    fn mouse_moved(&mut self, pos: &[f64; 2]) {
+        self.cursor = None;
+        self.pointer_moved(WindowPoint {
+            x: pos[0],
+            y: pos[1],
+        });
+    }
+
+    fn touched(&mut self, args: &TouchArgs) {
+        self.cursor = None;
+        let phase = match args.touch {
+            Touch::Start => TouchPhase::Start,
+            Touch::Move => TouchPhase::Move,
+            Touch::End => TouchPhase::End,
+            Touch::Cancel => TouchPhase::Cancel,
+        };
+        let [x, y] = args.position();
+        match self.touch.handle(args.id, phase, WindowPoint { x, y }) {
+            Some(TouchAction::Press(position)) => {
+                self.pointer_moved(position);
+                self.pointer_down(TOUCH_MARGIN);
+            }
+            Some(TouchAction::Move(position)) => self.pointer_moved(position),
+            Some(TouchAction::Release(position)) => {
+                self.pointer_moved(position);
+                self.pointer_up();
+            }
+            Some(TouchAction::Cancel) => self.cancel_drag(),
+            Some(TouchAction::Undo) => self.undo(),
+            Some(TouchAction::Redo) => self.redo(),
+            None => {}
+        }
     }
 }
 
 struct App {
     gl: GlGraphics,
     play_area: PlayArea,
+    bindings: Bindings,
+    settings: Settings,
+    settings_screen: Option<SettingsScreen>,
+    campaign: Campaign,
+    campaign_screen: Option<CampaignScreen>,
+    window_size: [f64; 2],
+    // Set when the window mode, size, vsync or frame rate has to change
+    display_changed: bool,
+    quit: bool,
 }
 
 impl App {
     fn render(&mut self, args: &RenderArgs) {
-        self.gl.draw(args.viewport(), |c, gl| {
+        self.window_size = args.window_size;
+        self.gl.draw(args.viewport(), |_c, gl| {
             // Clear the screen.
-            graphics::clear(COLOR_BACKGROUND, gl);
-
-            self.play_area.render(
-                args,
-                place_play_area(args.window_size[0], args.window_size[1]),
-            );
+            let background = if self.play_area.disc_style.high_contrast {
+                COLOR_HC_BACKGROUND
+            } else {
+                rgb_to_gl(self.play_area.theme.background)
+            };
+            graphics::clear(background, gl);
         });
+
+        self.play_area.render(
+            args,
+            Viewport::centered(args.window_size[0], args.window_size[1]),
+        );
+
+        if let Some(screen) = &self.settings_screen {
+            self.gl.draw(args.viewport(), |c, gl| {
+                screen.render(&self.settings, args.window_size, c, gl);
+            });
+        }
+        if let Some(screen) = &self.campaign_screen {
+            self.gl.draw(args.viewport(), |c, gl| {
+                screen.render(&self.campaign, args.window_size, c, gl);
+            });
+        }
     }
 
-    fn update(&mut self, args: &UpdateArgs) {}
+    fn update(&mut self, args: &UpdateArgs) {
+        self.play_area.update(args);
+    }
 
     fn mouse_moved(&mut self, pos: &[f64; 2]) {
         self.play_area.mouse_moved(pos);
     }
 
-    fn mouse_button_pressed(&mut self, button: &MouseButton) {
-        self.play_area.mouse_button_pressed(button);
+    fn touched(&mut self, args: &TouchArgs) {
+        if self.settings_screen.is_none() && self.campaign_screen.is_none() {
+            self.play_area.touched(args);
+        }
     }
 
-    fn mouse_button_released(&mut self, button: &MouseButton) {
-        self.play_area.mouse_button_released(button);
+    fn button_pressed(&mut self, button: &Button) {
+        let action = self.bindings.action(Binding::from_button(button));
+        if action == Some(Action::ToggleFullscreen) {
+            self.toggle_fullscreen();
+            return;
+        }
+        if self.settings_screen.is_some() {
+            if let Some(action) = action {
+                self.settings_action(action, matches!(button, Button::Mouse(_)));
+            }
+            return;
+        }
+        if self.campaign_screen.is_some() {
+            if let Some(action) = action {
+                self.campaign_action(action, matches!(button, Button::Mouse(_)));
+            }
+            return;
+        }
+
+        match (button, action) {
+            // Mouse buttons pick up whatever is under the pointer
+            (Button::Mouse(_), Some(Action::PickOrPlace)) => self.play_area.pointer_down(0.0),
+            (_, Some(Action::Quit)) => self.quit = true,
+            (_, Some(Action::Settings)) => {
+                self.play_area.cancel_drag();
+                self.settings_screen = Some(SettingsScreen::new());
+            }
+            (_, Some(Action::Campaign)) => {
+                self.play_area.cancel_drag();
+                self.campaign_screen = Some(CampaignScreen::new(&self.campaign));
+            }
+            (_, Some(action)) => {
+                self.play_area.perform(action);
+
+                // Remember themes picked with the theme key
+                let name = &self.play_area.theme.name;
+                if action == Action::NextTheme && Theme::builtin(name).is_some() {
+                    self.settings.theme = name.clone();
+                    self.settings.save();
+                }
+            }
+            (_, None) => {}
+        }
+    }
+
+    fn button_released(&mut self, button: &Button) {
+        if let (Button::Mouse(_), Some(Action::PickOrPlace), None, None) = (
+            button,
+            self.bindings.action(Binding::from_button(button)),
+            &self.settings_screen,
+            &self.campaign_screen,
+        ) {
+            self.play_area.pointer_up();
+        }
+    }
+
+    fn settings_action(&mut self, action: Action, mouse: bool) {
+        let Some(screen) = &mut self.settings_screen else {
+            return;
+        };
+        let before = self.settings.clone();
+
+        match action {
+            Action::SelectUp => screen.select(-1),
+            Action::SelectDown => screen.select(1),
+            Action::SelectLeft => screen.change(&mut self.settings, -1),
+            Action::SelectRight => screen.change(&mut self.settings, 1),
+            Action::PickOrPlace if mouse => {
+                let pos = [self.play_area.mouse.x, self.play_area.mouse.y];
+                if !screen.click(&mut self.settings, self.window_size, pos) {
+                    self.settings_screen = None;
+                }
+            }
+            Action::PickOrPlace => screen.change(&mut self.settings, 1),
+            Action::Cancel | Action::Settings => self.settings_screen = None,
+            Action::Quit => self.quit = true,
+            _ => {}
+        }
+
+        if self.settings != before {
+            self.apply_settings(&before);
+            self.settings.save();
+        }
+    }
+
+    // Picks a level and plays it
+    fn campaign_action(&mut self, action: Action, mouse: bool) {
+        let Some(screen) = &mut self.campaign_screen else {
+            return;
+        };
+
+        let level = match action {
+            Action::SelectUp => {
+                screen.select(&self.campaign, -1);
+                None
+            }
+            Action::SelectDown => {
+                screen.select(&self.campaign, 1);
+                None
+            }
+            Action::PickOrPlace if mouse => {
+                let pos = [self.play_area.mouse.x, self.play_area.mouse.y];
+                if screen.click(&self.campaign, self.window_size, pos) {
+                    Some(screen.selected())
+                } else {
+                    self.campaign_screen = None;
+                    None
+                }
+            }
+            Action::PickOrPlace => Some(screen.selected()),
+            Action::Cancel | Action::Campaign => {
+                self.campaign_screen = None;
+                None
+            }
+            Action::Quit => {
+                self.quit = true;
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(index) = level {
+            self.campaign_screen = None;
+            let challenge = Challenge::level(index, &self.campaign.levels[index]);
+            self.play_area.start_challenge(challenge);
+        }
+    }
+
+    // Puts changed settings into effect straight away
+    fn apply_settings(&mut self, before: &Settings) {
+        let settings = &self.settings;
+        if (settings.discs, settings.rods) != (before.discs, before.rods) {
+            self.play_area.new_game(settings.discs, settings.rods);
+        }
+        if settings.theme != before.theme {
+            match Theme::load(&settings.theme) {
+                Ok(theme) => self.play_area.apply_theme(theme),
+                Err(err) => eprintln!("{}", err),
+            }
+        }
+        self.play_area.animation_speed = settings.animation_speed;
+        self.play_area.input_mode = settings.input_mode;
+        let display = |s: &Settings| {
+            (
+                s.window_width,
+                s.window_height,
+                s.window_mode,
+                s.vsync,
+                s.max_fps,
+            )
+        };
+        self.display_changed = display(settings) != display(before);
+    }
+
+    // F11 switches between a window and borderless fullscreen
+    fn toggle_fullscreen(&mut self) {
+        let before = self.settings.clone();
+        self.settings.window_mode = match self.settings.window_mode {
+            WindowMode::Windowed => WindowMode::Borderless,
+            _ => WindowMode::Windowed,
+        };
+        self.apply_settings(&before);
+        self.settings.save();
+    }
+
+    fn apply_display(&mut self, window: &mut Window, events: &mut Events) {
+        self.display_changed = false;
+        let settings = &self.settings;
+
+        let fullscreen = match settings.window_mode {
+            WindowMode::Windowed => None,
+            WindowMode::Borderless => Some(Fullscreen::Borderless(None)),
+            // The biggest, then fastest, video mode of the window's monitor
+            WindowMode::Fullscreen => Some(
+                window
+                    .window
+                    .current_monitor()
+                    .and_then(|monitor| {
+                        monitor.video_modes().max_by_key(|mode| {
+                            let size = mode.size();
+                            (size.width * size.height, mode.refresh_rate_millihertz())
+                        })
+                    })
+                    .map_or(Fullscreen::Borderless(None), Fullscreen::Exclusive),
+            ),
+        };
+        if fullscreen.is_none() {
+            window.set_size([settings.window_width, settings.window_height]);
+        }
+        window.window.set_fullscreen(fullscreen);
+
+        let interval = if settings.vsync {
+            SwapInterval::Wait(NonZeroU32::MIN)
+        } else {
+            SwapInterval::DontWait
+        };
+        if let Err(err) = window.surface.set_swap_interval(&window.ctx, interval) {
+            eprintln!("could not change vsync: {}", err);
+        }
+        events.set_max_fps(settings.max_fps);
+    }
+
+    fn stick_moved(&mut self, args: &ControllerAxisArgs) {
+        if self.settings_screen.is_none() && self.campaign_screen.is_none() {
+            self.play_area.stick_moved(args);
+        }
     }
 }
 
-fn init_discs(n_discs: u32, n_rods: u32) -> Vec<Vec<Disc>> {
-    let mut discs: Vec<Vec<Disc>> = vec![];
-    let mut internal: Vec<Disc> = vec![];
+// Multiplies a colour into a tint
+fn tint_with(tint: [f32; 4], color: RGBColor) -> [f32; 4] {
+    tint_gl(tint, rgb_to_gl(color))
+}
 
-    let width_step = (DISC_WIDTH_MAX - DISC_WIDTH_MIN) / (n_discs - 1) as f64;
+fn init_discs(n_discs: u32, theme: &Theme, textures: &mut TextureManager) -> Vec<Disc> {
+    let mut discs: Vec<Disc> = vec![];
 
-    // Add discs to first rod
-    for n in (0..n_discs).rev() {
-        internal.push(Disc {
-            width: DISC_WIDTH_MIN + width_step * n as f64,
+    let art = *textures.art();
+
+    for n in 0..n_discs {
+        let width = disc_width(n, n_discs);
+        let (texture, tint) = textures.disc_texture(
+            art.art_pixels(width),
+            art.art_pixels(DISC_HEIGHT),
+            theme.disc_color(n, n_discs),
+        );
+        discs.push(Disc {
             value: n,
+            width,
+            pixel_size: art.pixel_size,
             highlighted: false,
-            texture: load_disc_texture_color(compute_disc_color(n, n_discs)),
+            texture,
+            tint,
+            highlight_tint: tint_with(tint, theme.highlight_tint),
         })
     }
-    discs.push(internal);
-
-    // Fill remaining rods
-    for _n in 1..n_rods {
-        discs.push(vec![]);
-    }
 
     discs
 }
 
-fn init_rods(n_rods: u32) -> Vec<Rod> {
+fn init_rods(n_rods: u32, theme: &Theme, textures: &mut TextureManager) -> Vec<Rod> {
     let mut rods: Vec<Rod> = vec![];
 
-    let screen_divs = WINDOW_WIDTH as f64 / n_rods as f64;
-
-    for n in 0..n_rods {
+    let art = *textures.art();
+    let texture = textures.rod_texture(
+        &theme.rod,
+        art.art_pixels(ROD_WIDTH),
+        art.art_pixels(ROD_HEIGHT),
+    );
+    let tint = rgb_to_gl(theme.rod.tint);
+    let highlight_tint = rgb_to_gl(multiply(theme.rod.tint, theme.highlight_tint));
+
+    for n in 0..n_rods as usize {
+        let [pos_x, pos_y, width, height] = rod_rect(n, n_rods as usize);
         rods.push(Rod {
-            width: ROD_WIDTH,
-            height: ROD_HEIGHT,
-            pos_x: screen_divs / 2.0 + screen_divs * n as f64 - ROD_WIDTH / 2.0,
-            pos_y: ROD_TOP,
-            center: screen_divs / 2.0 + screen_divs * n as f64,
-            highlighted: false,
-            dropbox_start: screen_divs * n as f64,
-            dropbox_end: screen_divs * (n + 1) as f64,
-            texture: load_rod_texture(),
+            width,
+            height,
+            pos_x,
+            pos_y,
+            center: rod_center(n, n_rods as usize),
+            drop_target: DropTarget::None,
+            texture: texture.clone(),
+            tint,
+            highlight_tint,
         })
     }
 
     rods
 }
 
+// Opens the game window. The window asks for OpenGL 3.2 and quietly settles
+// for a 2.1 context when the driver can't do better; winit can only start once
+// per process, so there is no second attempt with other settings.
+fn create_window(settings: &Settings) -> (Window, OpenGL) {
+    let window = WindowSettings::new(
+        WINDOW_TITLE,
+        [settings.window_width, settings.window_height],
+    )
+    .graphics_api(OpenGL::V3_2)
+    .vsync(settings.vsync)
+    .exit_on_esc(false)
+    .resizable(true)
+    .build();
+    let window: Window = match window {
+        Ok(window) => window,
+        Err(err) => {
+            eprintln!("hanoi: could not open the game window: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // Shaders have to match the context we actually got
+    let opengl = match context_version() {
+        Some(version) if version >= (3, 2) => OpenGL::V3_2,
+        _ => OpenGL::V2_1,
+    };
+    (window, opengl)
+}
+
+// Version of the current desktop OpenGL context, from a string like "3.2.0 Mesa
+// 23.0". An OpenGL ES context ("OpenGL ES 3.2 ...") has none, as its version
+// numbers don't match the desktop shaders.
+fn context_version() -> Option<(u32, u32)> {
+    let version = unsafe { gl::GetString(gl::VERSION) };
+    if version.is_null() {
+        return None;
+    }
+    let text = unsafe { CStr::from_ptr(version as *const c_char) }.to_string_lossy();
+    if text.starts_with("OpenGL ES") {
+        return None;
+    }
+    let mut numbers = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse().ok());
+    Some((numbers.next()?, numbers.next()?))
+}
+
+// Feeds one window or gamepad event to the app
+fn handle_input(app: &mut App, window: &mut Window, events: &mut Events, e: &Event) {
+    // Keyboard, mouse and gamepad buttons
+    if let Some(button) = e.press_args() {
+        app.button_pressed(&button);
+        if app.quit {
+            window.set_should_close(true);
+        }
+        if app.display_changed {
+            app.apply_display(window, events);
+        }
+    }
+    if let Some(button) = e.release_args() {
+        app.button_released(&button);
+    }
+    if let Some(args) = e.controller_axis_args() {
+        app.stick_moved(&args);
+    }
+
+    // Mouse movement events
+    e.mouse_cursor(|pos| {
+        app.mouse_moved(&pos);
+    });
+
+    // Touch events
+    if let Some(args) = e.touch_args() {
+        app.touched(&args);
+    }
+}
+
+// The state graph only fits small three-rod games
+fn init_state_graph(n_discs: u32, n_rods: usize) -> Option<StateGraph> {
+    if n_rods == GRAPH_RODS && n_discs <= GRAPH_MAX_DISCS {
+        Some(StateGraph::new(n_discs))
+    } else {
+        None
+    }
+}
+
 fn main() {
-    // Change this to OpenGL::V2_1 if not working.
-    let opengl = OpenGL::V3_2;
+    // Subcommands run headless, without creating a window
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let assets_dir = cli::take_assets_option(&mut args);
+    let theme_option = cli::take_theme_option(&mut args);
+    let bindings = cli::take_bindings_option(&mut args);
+    let campaign = cli::take_campaign_option(&mut args);
+    let disc_style = DiscStyle {
+        labels: cli::take_labels_option(&mut args),
+        high_contrast: cli::take_flag(&mut args, "--high-contrast"),
+    };
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args, &theme_option.unwrap_or_default()));
+    }
 
-    // Create a Glutin window.
-    let mut window: Window = WindowSettings::new(WINDOW_TITLE, [WINDOW_WIDTH, WINDOW_HEIGHT])
-        .graphics_api(opengl)
-        .exit_on_esc(true)
-        .resizable(true)
-        .build()
-        .unwrap();
+    let settings = Settings::load();
+    let theme = theme_option.unwrap_or_else(|| Theme::load(&settings.theme).unwrap_or_default());
+
+    let window_size = [settings.window_width, settings.window_height];
+    let (mut window, opengl) = create_window(&settings);
 
     // Initialize discs
-    let discs = init_discs(N_DISCS, N_RODS);
-    let rods = init_rods(N_RODS);
+    let game = Game::new(settings.discs, settings.rods);
+    let mut textures = TextureManager::new(Assets::discover(assets_dir), GPU_TINT, theme.art);
+    let discs = init_discs(settings.discs, &theme, &mut textures);
+    let rods = init_rods(settings.rods as u32, &theme, &mut textures);
+    let state_graph = init_state_graph(settings.discs, settings.rods);
 
-    let mut play_area = PlayArea {
+    let play_area = PlayArea {
         gl: GlGraphics::new(opengl),
+        theme,
+        textures,
+        disc_style,
+        game,
         discs,
         rods,
-        moving_disc: None,
-        mov_ofst_x: 0.0,
-        mov_ofst_y: 0.0,
-        mouse_pos_x: 0.0,
-        mouse_pos_y: 0.0,
-        start_rod: 0,
-        last_pari: PlayAreaRenderInfo {
-            width: 0.0,
-            height: 0.0,
-            x: 0.0,
-            y: 0.0,
-        },
+        mouse: WindowPoint { x: 0.0, y: 0.0 },
+        drag: None,
+        touch: TouchInput::default(),
+        cursor: None,
+        stick_pushed: false,
+        hint: None,
+        paused: false,
+        viewport: Viewport::centered(window_size[0] as f64, window_size[1] as f64),
+        state_graph,
+        show_state_graph: false,
+        history: History::default(),
+        auto_solve: None,
+        animation_speed: settings.animation_speed,
+        speedrun: None,
+        challenge: None,
+        awards: Awards::new(),
+        input_mode: settings.input_mode,
+        show_binary_overlay: false,
     };
 
     let mut app = App {
         gl: GlGraphics::new(opengl),
         play_area,
+        bindings,
+        settings: settings.clone(),
+        settings_screen: None,
+        campaign,
+        campaign_screen: None,
+        window_size: [window_size[0] as f64, window_size[1] as f64],
+        display_changed: false,
+        quit: false,
     };
 
     // Create a new game and run it.
 
     let mut events = Events::new(EventSettings::new());
+    let mut gamepads = Gamepads::new();
+    app.apply_display(&mut window, &mut events);
     while let Some(e) = events.next(&mut window) {
         if let Some(args) = e.render_args() {
             app.render(&args);
@@ -534,19 +1509,23 @@ fn main() {
 
         if let Some(args) = e.update_args() {
             app.update(&args);
+            // The window doesn't report gamepads, so they are read every update
+            for e in gamepads.poll() {
+                handle_input(&mut app, &mut window, &mut events, &e);
+            }
         }
 
-        // Mouse button events
-        if let Some(Button::Mouse(button)) = e.press_args() {
-            app.mouse_button_pressed(&button)
-        }
-        if let Some(Button::Mouse(button)) = e.release_args() {
-            app.mouse_button_released(&button)
-        }
+        handle_input(&mut app, &mut window, &mut events, &e);
+    }
 
-        // Mouse movement events
-        e.mouse_cursor(|pos| {
-            app.mouse_moved(&pos);
-        });
+    // Keep the size the window was resized to
+    let size = window.size();
+    let settings = &mut app.settings;
+    let resized = (size.width as u32, size.height as u32);
+    if settings.window_mode == WindowMode::Windowed
+        && resized != (settings.window_width, settings.window_height)
+    {
+        (settings.window_width, settings.window_height) = resized;
+        settings.save();
     }
 }
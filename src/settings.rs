@@ -0,0 +1,388 @@
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use hanoi::game::{MAX_RODS, MIN_RODS};
+use hanoi::layout::MAX_BOARD_DISCS;
+use hanoi::theme::{Theme, BUILTIN_THEMES};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// User preferences, kept in the config directory and edited from the settings screen
+
+const SETTINGS_FOLDER: &str = "torri-di-hanoi";
+const SETTINGS_FILE: &str = "settings.toml";
+
+pub const MIN_DISCS: u32 = 1;
+
+const WINDOW_SIZES: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (1024, 576)];
+const ANIMATION_SPEEDS: [f64; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+const FPS_CAPS: [u64; 5] = [30, 60, 120, 144, 240];
+// Smallest window the game opens; the board and menus are scaled down to fit
+const MIN_WINDOW_SIZE: (u32, u32) = (320, 240);
+
+const COLOR_PANEL_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.85];
+const COLOR_TITLE: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const COLOR_ITEM: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const COLOR_SELECTED: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_SELECTED_BACKGROUND: [f32; 4] = [0.3, 0.25, 0.05, 1.0];
+
+const TEXT_SCALE: f64 = 3.0;
+const LINE_HEIGHT: f64 = GLYPH_HEIGHT as f64 * TEXT_SCALE + 12.0;
+const PANEL_WIDTH: f64 = 600.0;
+const PANEL_PADDING: f64 = 20.0;
+// Space above a row's text inside its highlight
+const ROW_PADDING: f64 = 6.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputMode {
+    // Hold the button down while moving the disc
+    Drag,
+    // Click a disc to pick it up, then click a rod to put it down
+    Click,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    Windowed,
+    // A window covering the whole screen, quick to switch in and out of
+    Borderless,
+    // Takes over the monitor at its best video mode
+    Fullscreen,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub window_mode: WindowMode,
+    pub vsync: bool,
+    pub max_fps: u64,
+    pub discs: u32,
+    pub rods: usize,
+    pub theme: String,
+    // Multiplies the speed of the solver's animation
+    pub animation_speed: f64,
+    pub input_mode: InputMode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window_width: WINDOW_SIZES[0].0,
+            window_height: WINDOW_SIZES[0].1,
+            window_mode: WindowMode::Windowed,
+            vsync: true,
+            max_fps: 60,
+            discs: 5,
+            rods: 3,
+            theme: String::from("rainbow"),
+            animation_speed: 1.0,
+            input_mode: InputMode::Drag,
+        }
+    }
+}
+
+// `$XDG_CONFIG_HOME`, `~/.config`, `~/Library/Application Support` or `%APPDATA%`
+fn config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return env::var_os("APPDATA").map(PathBuf::from);
+    }
+    let home = env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "macos") {
+        return home.map(|home| home.join("Library/Application Support"));
+    }
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| home.join(".config")))
+}
+
+// Where the game keeps `file`, next to its settings
+pub fn config_path(file: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SETTINGS_FOLDER).join(file))
+}
+
+pub fn settings_path() -> Option<PathBuf> {
+    config_path(SETTINGS_FILE)
+}
+
+impl Settings {
+    // Missing settings are defaults; a broken file is reported and replaced by defaults
+    pub fn load() -> Settings {
+        let Some(path) = settings_path() else {
+            return Settings::default();
+        };
+        let Ok(text) = fs::read_to_string(&path) else {
+            return Settings::default();
+        };
+        let mut settings: Settings = toml::from_str(&text).unwrap_or_else(|err| {
+            eprintln!("ignoring {}: {}", path.display(), err);
+            Settings::default()
+        });
+
+        if let Err(err) = Theme::load(&settings.theme) {
+            eprintln!("ignoring theme in {}: {}", path.display(), err);
+            settings.theme = Settings::default().theme;
+        }
+        settings.discs = settings.discs.clamp(MIN_DISCS, MAX_BOARD_DISCS);
+        settings.rods = settings.rods.clamp(MIN_RODS, MAX_RODS);
+        settings.window_width = settings.window_width.max(MIN_WINDOW_SIZE.0);
+        settings.window_height = settings.window_height.max(MIN_WINDOW_SIZE.1);
+        // The event loop divides by it
+        settings.max_fps = settings.max_fps.max(1);
+        let speeds = ANIMATION_SPEEDS[0]..=ANIMATION_SPEEDS[ANIMATION_SPEEDS.len() - 1];
+        if !speeds.contains(&settings.animation_speed) {
+            eprintln!("ignoring animation speed in {}", path.display());
+            settings.animation_speed = Settings::default().animation_speed;
+        }
+        settings
+    }
+
+    pub fn save(&self) {
+        let Some(path) = settings_path() else {
+            return;
+        };
+        write_config(
+            &path,
+            &toml::to_string(self).expect("settings are always valid TOML"),
+        );
+    }
+}
+
+// Writes a file in the config directory, creating folders as needed
+pub fn write_config(path: &Path, text: &str) {
+    let result = fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(path, text));
+    if let Err(err) = result {
+        eprintln!("could not save {}: {}", path.display(), err);
+    }
+}
+
+// Reads a file from the config directory; a missing one is the default, and a
+// broken one is reported and treated as the default
+pub fn read_config<T: Default, E: fmt::Display>(
+    file: &str,
+    from_toml: impl Fn(&str) -> Result<T, E>,
+) -> T {
+    let Some(path) = config_path(file) else {
+        return T::default();
+    };
+    let Ok(text) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+    from_toml(&text).unwrap_or_else(|err| {
+        eprintln!("ignoring {}: {}", path.display(), err);
+        T::default()
+    })
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Field {
+    WindowSize,
+    WindowMode,
+    Vsync,
+    MaxFps,
+    Discs,
+    Rods,
+    Theme,
+    AnimationSpeed,
+    InputMode,
+}
+
+const FIELDS: [Field; 9] = [
+    Field::WindowSize,
+    Field::WindowMode,
+    Field::Vsync,
+    Field::MaxFps,
+    Field::Discs,
+    Field::Rods,
+    Field::Theme,
+    Field::AnimationSpeed,
+    Field::InputMode,
+];
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+// Position of `value` in `options` moved by `step`, wrapping around.
+// Values that aren't an option start from the first one.
+fn cycle<T: PartialEq + Copy>(options: &[T], value: T, step: i32) -> T {
+    match options.iter().position(|option| *option == value) {
+        Some(i) => options[(i as i32 + step).rem_euclid(options.len() as i32) as usize],
+        None => options[0],
+    }
+}
+
+fn label(field: Field, settings: &Settings) -> String {
+    match field {
+        Field::WindowSize => format!(
+            "Window size   {}x{}",
+            settings.window_width, settings.window_height
+        ),
+        Field::WindowMode => format!(
+            "Window mode   {}",
+            match settings.window_mode {
+                WindowMode::Windowed => "windowed",
+                WindowMode::Borderless => "borderless",
+                WindowMode::Fullscreen => "fullscreen",
+            }
+        ),
+        Field::Vsync => format!("Vsync         {}", on_off(settings.vsync)),
+        Field::MaxFps => format!("FPS cap       {}", settings.max_fps),
+        Field::Discs => format!("Discs         {}", settings.discs),
+        Field::Rods => format!("Rods          {}", settings.rods),
+        Field::Theme => format!("Theme         {}", settings.theme),
+        Field::AnimationSpeed => format!("Solver speed  x{}", settings.animation_speed),
+        Field::InputMode => format!(
+            "Input         {}",
+            match settings.input_mode {
+                InputMode::Drag => "drag",
+                InputMode::Click => "click to place",
+            }
+        ),
+    }
+}
+
+fn change(field: Field, settings: &mut Settings, step: i32) {
+    match field {
+        Field::WindowSize => {
+            let size = (settings.window_width, settings.window_height);
+            (settings.window_width, settings.window_height) = cycle(&WINDOW_SIZES, size, step);
+        }
+        Field::WindowMode => {
+            let modes = [
+                WindowMode::Windowed,
+                WindowMode::Borderless,
+                WindowMode::Fullscreen,
+            ];
+            settings.window_mode = cycle(&modes, settings.window_mode, step)
+        }
+        Field::Vsync => settings.vsync = !settings.vsync,
+        Field::MaxFps => settings.max_fps = cycle(&FPS_CAPS, settings.max_fps, step),
+        Field::Discs => {
+            settings.discs = (settings.discs as i32 + step)
+                .clamp(MIN_DISCS as i32, MAX_BOARD_DISCS as i32) as u32
+        }
+        Field::Rods => {
+            settings.rods =
+                (settings.rods as i32 + step).clamp(MIN_RODS as i32, MAX_RODS as i32) as usize
+        }
+        Field::Theme => {
+            let names = BUILTIN_THEMES.map(|(name, _)| name);
+            settings.theme = cycle(&names, settings.theme.as_str(), step).to_string();
+        }
+        Field::AnimationSpeed => {
+            settings.animation_speed = cycle(&ANIMATION_SPEEDS, settings.animation_speed, step)
+        }
+        Field::InputMode => {
+            settings.input_mode = match settings.input_mode {
+                InputMode::Drag => InputMode::Click,
+                InputMode::Click => InputMode::Drag,
+            }
+        }
+    }
+}
+
+// The in-game screen, drawn over the board
+pub struct SettingsScreen {
+    selected: usize,
+}
+
+impl SettingsScreen {
+    pub fn new() -> SettingsScreen {
+        SettingsScreen { selected: 0 }
+    }
+
+    pub fn select(&mut self, step: i32) {
+        self.selected = (self.selected as i32 + step).rem_euclid(FIELDS.len() as i32) as usize;
+    }
+
+    // Changes the selected setting
+    pub fn change(&self, settings: &mut Settings, step: i32) {
+        change(FIELDS[self.selected], settings, step);
+    }
+
+    fn panel(window_size: [f64; 2]) -> [f64; 4] {
+        let height = PANEL_PADDING * 2.0 + LINE_HEIGHT * (FIELDS.len() + 2) as f64;
+        [
+            (window_size[0] - PANEL_WIDTH) / 2.0,
+            (window_size[1] - height) / 2.0,
+            PANEL_WIDTH,
+            height,
+        ]
+    }
+
+    fn row_y(panel: [f64; 4], row: usize) -> f64 {
+        panel[1] + PANEL_PADDING + LINE_HEIGHT * (row + 2) as f64
+    }
+
+    // Selects and changes the setting clicked on, returning whether there was one
+    pub fn click(&mut self, settings: &mut Settings, window_size: [f64; 2], pos: [f64; 2]) -> bool {
+        let panel = SettingsScreen::panel(window_size);
+        if pos[0] < panel[0] || pos[0] > panel[0] + panel[2] {
+            return false;
+        }
+        let row = (0..FIELDS.len()).find(|row| {
+            let y = SettingsScreen::row_y(panel, *row);
+            pos[1] >= y - ROW_PADDING && pos[1] < y - ROW_PADDING + LINE_HEIGHT
+        });
+        match row {
+            Some(row) => {
+                self.selected = row;
+                self.change(settings, 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn render(
+        &self,
+        settings: &Settings,
+        window_size: [f64; 2],
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+    ) {
+        let panel = SettingsScreen::panel(window_size);
+        graphics::rectangle(COLOR_PANEL_BACKGROUND, panel, c.transform, gl);
+
+        let title = "Settings";
+        draw_text(
+            title,
+            panel[0] + (PANEL_WIDTH - text_width(title, TEXT_SCALE)) / 2.0,
+            panel[1] + PANEL_PADDING,
+            TEXT_SCALE,
+            COLOR_TITLE,
+            c,
+            gl,
+        );
+
+        for (row, field) in FIELDS.iter().enumerate() {
+            let y = SettingsScreen::row_y(panel, row);
+            let color = if row == self.selected {
+                let highlight = [panel[0], y - ROW_PADDING, PANEL_WIDTH, LINE_HEIGHT];
+                graphics::rectangle(COLOR_SELECTED_BACKGROUND, highlight, c.transform, gl);
+                COLOR_SELECTED
+            } else {
+                COLOR_ITEM
+            };
+            draw_text(
+                &label(*field, settings),
+                panel[0] + PANEL_PADDING,
+                y,
+                TEXT_SCALE,
+                color,
+                c,
+                gl,
+            );
+        }
+    }
+}
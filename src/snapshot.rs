@@ -0,0 +1,107 @@
+// Draws boards into images on the CPU, without OpenGL, for the `render` command and tests.
+// Uses the same layout as the game window; rods always use the generated art, since
+// image files are looked up by the game itself.
+
+use crate::color::{multiply, RGBColor};
+use crate::font::for_each_pixel;
+use crate::game::Board;
+use crate::layout::{disc_rect, disc_width, rod_rect, BOARD_HEIGHT, BOARD_WIDTH, DISC_HEIGHT};
+use crate::theme::Theme;
+use image::{Rgba, RgbaImage};
+
+fn blend(under: Rgba<u8>, over: [u8; 4]) -> Rgba<u8> {
+    let alpha = over[3] as u32;
+    let mix = |a: u8, b: u8| ((a as u32 * (255 - alpha) + b as u32 * alpha) / 255) as u8;
+    Rgba([
+        mix(under[0], over[0]),
+        mix(under[1], over[1]),
+        mix(under[2], over[2]),
+        255,
+    ])
+}
+
+// Stretches `image` over `rect` with nearest neighbour sampling, multiplied by `tint`
+fn draw_image(canvas: &mut RgbaImage, image: &RgbaImage, rect: [f64; 4], tint: RGBColor) {
+    let x0 = rect[0].round() as i64;
+    let y0 = rect[1].round() as i64;
+    let x1 = (rect[0] + rect[2]).round() as i64;
+    let y1 = (rect[1] + rect[3]).round() as i64;
+    if x1 <= x0 || y1 <= y0 {
+        return;
+    }
+
+    for y in y0.max(0)..y1.min(canvas.height() as i64) {
+        let sy = ((y - y0) * image.height() as i64 / (y1 - y0)) as u32;
+        for x in x0.max(0)..x1.min(canvas.width() as i64) {
+            let sx = ((x - x0) * image.width() as i64 / (x1 - x0)) as u32;
+            let [r, g, b, a] = image.get_pixel(sx, sy).0;
+            let [r, g, b] = multiply([r, g, b], tint);
+
+            let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+            *pixel = blend(*pixel, [r, g, b, a]);
+        }
+    }
+}
+
+pub fn render_board(board: &Board, theme: &Theme) -> RgbaImage {
+    render_frame(board, theme, None)
+}
+
+// Like `render_board`, but with the top disc of rod `floating.0` drawn with its top
+// centre at `floating.1` instead of on its stack, as when it is being moved
+pub fn render_frame(
+    board: &Board,
+    theme: &Theme,
+    floating: Option<(usize, [f64; 2])>,
+) -> RgbaImage {
+    let [r, g, b] = theme.play_area_background;
+    let mut canvas = RgbaImage::from_pixel(BOARD_WIDTH, BOARD_HEIGHT, Rgba([r, g, b, 255]));
+
+    let art = theme.art;
+    let n_rods = board.n_rods();
+    let n_discs = board.n_discs();
+
+    for rod in 0..n_rods {
+        let rect = rod_rect(rod, n_rods);
+        let image = art.rod_image(art.art_pixels(rect[2]), art.art_pixels(rect[3]), false);
+        draw_image(&mut canvas, &image, rect, theme.rod.tint);
+    }
+
+    let mut draw_disc = |disc: u32, rect: [f64; 4]| {
+        let image = art.disc_image(art.art_pixels(rect[2]), art.art_pixels(DISC_HEIGHT), false);
+        draw_image(&mut canvas, &image, rect, theme.disc_color(disc, n_discs));
+    };
+
+    for (rod, discs) in board.rods().iter().enumerate() {
+        for (level, disc) in discs.iter().enumerate() {
+            if floating.is_some_and(|(from, _)| from == rod) && level == discs.len() - 1 {
+                continue;
+            }
+            draw_disc(*disc, disc_rect(*disc, n_discs, rod, n_rods, level as u32));
+        }
+    }
+
+    if let Some((rod, [x, y])) = floating {
+        if let Some(disc) = board.top(rod) {
+            let width = disc_width(disc, n_discs);
+            draw_disc(disc, [x - width / 2.0, y, width, DISC_HEIGHT]);
+        }
+    }
+
+    canvas
+}
+
+// Writes `text` in the pixel font, each font pixel `scale` image pixels wide
+pub fn draw_text(canvas: &mut RgbaImage, text: &str, x: u32, y: u32, scale: u32, color: RGBColor) {
+    let [r, g, b] = color;
+    for_each_pixel(text, |px, py| {
+        for dy in 0..scale {
+            for dx in 0..scale {
+                let (cx, cy) = (x + px * scale + dx, y + py * scale + dy);
+                if cx < canvas.width() && cy < canvas.height() {
+                    canvas.put_pixel(cx, cy, Rgba([r, g, b, 255]));
+                }
+            }
+        }
+    });
+}
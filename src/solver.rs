@@ -0,0 +1,263 @@
+use crate::game::{rod_name, Move};
+
+// Recursive solver, recorded as a trace so it can be animated step by step
+
+pub struct Frame {
+    pub n: u32,
+    pub from: usize,
+    pub to: usize,
+    pub via: usize,
+    pub depth: u32,
+    pub parent: Option<usize>,
+    // Range of moves emitted by this call and its children
+    pub first_move: usize,
+    pub last_move: usize,
+    // The single move made directly by this call
+    pub own_move: usize,
+}
+
+#[derive(Copy, Clone)]
+pub enum Step {
+    Enter(usize),
+    Move(usize),
+    Exit(usize),
+}
+
+pub struct Trace {
+    pub frames: Vec<Frame>,
+    pub moves: Vec<Move>,
+    pub steps: Vec<Step>,
+}
+
+impl Frame {
+    pub fn call(&self) -> String {
+        format!(
+            "hanoi({},{},{},{})",
+            self.n,
+            rod_name(self.from),
+            rod_name(self.to),
+            rod_name(self.via)
+        )
+    }
+}
+
+impl Trace {
+    pub fn new(n_discs: u32, from: usize, to: usize, via: usize) -> Trace {
+        let mut trace = Trace {
+            frames: vec![],
+            moves: vec![],
+            steps: vec![],
+        };
+        trace.hanoi(n_discs, from, to, via, 0, None);
+        trace
+    }
+
+    fn hanoi(
+        &mut self,
+        n: u32,
+        from: usize,
+        to: usize,
+        via: usize,
+        depth: u32,
+        parent: Option<usize>,
+    ) {
+        if n == 0 {
+            return;
+        }
+
+        let frame = self.frames.len();
+        self.frames.push(Frame {
+            n,
+            from,
+            to,
+            via,
+            depth,
+            parent,
+            first_move: self.moves.len(),
+            last_move: 0,
+            own_move: 0,
+        });
+        self.steps.push(Step::Enter(frame));
+
+        self.hanoi(n - 1, from, via, to, depth + 1, Some(frame));
+
+        self.frames[frame].own_move = self.moves.len();
+        self.moves.push(Move {
+            disc: n - 1,
+            from,
+            to,
+        });
+        self.steps.push(Step::Move(frame));
+
+        self.hanoi(n - 1, via, to, from, depth + 1, Some(frame));
+
+        self.frames[frame].last_move = self.moves.len() - 1;
+        self.steps.push(Step::Exit(frame));
+    }
+}
+
+// Move counts and split points of the optimal solution for any number of rods.
+// With more than three rods this is the Frame-Stewart algorithm: move the top
+// `split` discs out of the way using every rod, move the rest with one rod less,
+// then put the top discs back on top.
+pub struct TowerPlan {
+    n_discs: u32,
+    n_rods: usize,
+    // Indexed by [rods - 3][discs]; None when the count doesn't fit in a u128
+    counts: Vec<Vec<Option<u128>>>,
+    splits: Vec<Vec<u32>>,
+}
+
+impl TowerPlan {
+    pub fn new(n_discs: u32, n_rods: usize) -> TowerPlan {
+        assert!(n_rods >= 3, "at least three rods are needed");
+
+        let n = n_discs as usize;
+        let mut counts: Vec<Vec<Option<u128>>> = vec![];
+        let mut splits: Vec<Vec<u32>> = vec![];
+
+        // Three rods: the classic 2^n - 1, always splitting off all but the last disc
+        counts.push(
+            (0..=n_discs)
+                .map(|d| 1u128.checked_shl(d).map(|p| p - 1))
+                .collect(),
+        );
+        splits.push((0..=n_discs).map(|d| d.saturating_sub(1)).collect());
+
+        for rods in 4..=n_rods {
+            let fewer = &counts[rods - 4];
+            let mut rod_counts: Vec<Option<u128>> = vec![Some(0); n + 1];
+            let mut rod_splits: Vec<u32> = vec![0; n + 1];
+
+            for d in 1..=n {
+                let mut best: Option<(u128, u32)> = None;
+                for t in 1..d {
+                    let count = rod_counts[t]
+                        .and_then(|top| top.checked_mul(2))
+                        .and_then(|top| fewer[d - t].and_then(|rest| top.checked_add(rest)));
+                    if let Some(count) = count {
+                        if best.is_none_or(|(best_count, _)| count < best_count) {
+                            best = Some((count, t as u32));
+                        }
+                    }
+                }
+                if d == 1 {
+                    best = Some((1, 0));
+                }
+                rod_counts[d] = best.map(|(count, _)| count);
+                rod_splits[d] = best.map_or(d as u32 - 1, |(_, t)| t);
+            }
+
+            counts.push(rod_counts);
+            splits.push(rod_splits);
+        }
+
+        TowerPlan {
+            n_discs,
+            n_rods,
+            counts,
+            splits,
+        }
+    }
+
+    // Total number of moves, None if it doesn't fit in a u128
+    pub fn total(&self) -> Option<u128> {
+        self.counts[self.n_rods - 3][self.n_discs as usize]
+    }
+
+    // Calls `emit` with the index (from 0) and the move for every move of the
+    // solution inside `range`, without ever holding more than one move in memory
+    pub fn stream<E>(
+        &self,
+        from: usize,
+        to: usize,
+        range: std::ops::Range<u128>,
+        emit: &mut impl FnMut(u128, Move) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let spares: Vec<usize> = (0..self.n_rods)
+            .filter(|rod| *rod != from && *rod != to)
+            .collect();
+        self.stream_tower(self.n_discs, 0, from, to, &spares, 0, &range, emit)?;
+        Ok(())
+    }
+
+    // Streams the moves of a tower of `n` discs, the smallest being `base`,
+    // returning the number of moves it takes
+    #[allow(clippy::too_many_arguments)]
+    fn stream_tower<E>(
+        &self,
+        n: u32,
+        base: u32,
+        from: usize,
+        to: usize,
+        spares: &[usize],
+        offset: u128,
+        range: &std::ops::Range<u128>,
+        emit: &mut impl FnMut(u128, Move) -> Result<(), E>,
+    ) -> Result<u128, E> {
+        if n == 0 {
+            return Ok(0);
+        }
+        if n == 1 {
+            if range.contains(&offset) {
+                emit(
+                    offset,
+                    Move {
+                        disc: base,
+                        from,
+                        to,
+                    },
+                )?;
+            }
+            return Ok(1);
+        }
+
+        // Skip whole subtrees that lie outside the requested range
+        let count = self.counts[spares.len() - 1][n as usize];
+        if let Some(count) = count {
+            if offset >= range.end || offset.saturating_add(count) <= range.start {
+                return Ok(count);
+            }
+        }
+        if offset >= range.end {
+            return Ok(0);
+        }
+
+        let split = self.splits[spares.len() - 1][n as usize];
+        let via = spares[0];
+
+        // Top discs out of the way, using every rod
+        let mut top_spares: Vec<usize> = vec![to];
+        top_spares.extend_from_slice(&spares[1..]);
+        let mut done =
+            self.stream_tower(split, base, from, via, &top_spares, offset, range, emit)?;
+
+        // The rest straight to the target, without the rod holding the top discs
+        done = done.saturating_add(self.stream_tower(
+            n - split,
+            base + split,
+            from,
+            to,
+            &spares[1..],
+            offset.saturating_add(done),
+            range,
+            emit,
+        )?);
+
+        // Top discs back on top
+        let mut back_spares: Vec<usize> = vec![from];
+        back_spares.extend_from_slice(&spares[1..]);
+        done = done.saturating_add(self.stream_tower(
+            split,
+            base,
+            via,
+            to,
+            &back_spares,
+            offset.saturating_add(done),
+            range,
+            emit,
+        )?);
+
+        Ok(done)
+    }
+}
@@ -0,0 +1,204 @@
+// Timed runs: the moves of a run with the time they were made, per-disc
+// splits, personal bests and the replay files runs are saved to
+
+use crate::game::{parse_rod, rod_name, Game, Move, MAX_DISCS, MAX_RODS, MIN_RODS};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimedMove {
+    // Seconds since the start of the run
+    pub time: f64,
+    pub mv: Move,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub n_discs: u32,
+    pub n_rods: usize,
+    pub moves: Vec<TimedMove>,
+}
+
+// How a run is written to a replay file
+#[derive(Deserialize, Serialize)]
+struct RunFile {
+    discs: u32,
+    rods: usize,
+    // Rod pairs like "AC"
+    moves: Vec<String>,
+    times: Vec<f64>,
+}
+
+impl Run {
+    pub fn new(n_discs: u32, n_rods: usize) -> Run {
+        Run {
+            n_discs,
+            n_rods,
+            moves: vec![],
+        }
+    }
+
+    pub fn record(&mut self, time: f64, mv: Move) {
+        self.moves.push(TimedMove { time, mv });
+    }
+
+    // Time at which every disc size first reached the target rod, smallest disc first
+    pub fn splits(&self) -> Vec<Option<f64>> {
+        let target = Game::new(self.n_discs, self.n_rods).target();
+        let mut splits = vec![None; self.n_discs as usize];
+        for timed in &self.moves {
+            let split = &mut splits[timed.mv.disc as usize];
+            if timed.mv.to == target && split.is_none() {
+                *split = Some(timed.time);
+            }
+        }
+        splits
+    }
+
+    // Plays the run back, returning its final time if every move was legal,
+    // the times never go backwards and the puzzle ends up solved
+    pub fn verify(&self) -> Result<f64, String> {
+        let mut game = Game::new(self.n_discs, self.n_rods);
+        let mut last_time = 0.0;
+        for (i, timed) in self.moves.iter().enumerate() {
+            let mv = timed.mv;
+            let played = game
+                .try_move(mv.from, mv.to)
+                .map_err(|err| format!("move {} is illegal: {}", i + 1, err))?;
+            if played.disc != mv.disc {
+                return Err(format!("move {} doesn't move disc {}", i + 1, mv.disc + 1));
+            }
+            if !timed.time.is_finite() {
+                return Err(format!("move {} has no valid time", i + 1));
+            }
+            if timed.time < last_time {
+                return Err(format!("move {} happens before the one before it", i + 1));
+            }
+            last_time = timed.time;
+        }
+
+        if game.is_solved() {
+            Ok(last_time)
+        } else {
+            Err(String::from(
+                "the puzzle isn't solved at the end of the run",
+            ))
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        let file = RunFile {
+            discs: self.n_discs,
+            rods: self.n_rods,
+            moves: self
+                .moves
+                .iter()
+                .map(|timed| format!("{}{}", rod_name(timed.mv.from), rod_name(timed.mv.to)))
+                .collect(),
+            times: self.moves.iter().map(|timed| timed.time).collect(),
+        };
+        toml::to_string(&file).expect("runs are always valid TOML")
+    }
+
+    // Disc numbers aren't stored, so they are worked out by playing the moves
+    pub fn from_toml(text: &str) -> Result<Run, String> {
+        let file: RunFile = toml::from_str(text).map_err(|err| err.to_string())?;
+        if file.moves.len() != file.times.len() {
+            return Err(String::from("there must be a time for every move"));
+        }
+        if !(1..=MAX_DISCS).contains(&file.discs) || !(MIN_RODS..=MAX_RODS).contains(&file.rods) {
+            return Err(format!(
+                "runs have 1 to {} discs on {} to {} rods",
+                MAX_DISCS, MIN_RODS, MAX_RODS
+            ));
+        }
+
+        let mut run = Run::new(file.discs, file.rods);
+        let mut game = Game::new(file.discs, file.rods);
+        for (i, (pair, time)) in file.moves.iter().zip(file.times).enumerate() {
+            let mut rods = pair.chars().map(parse_rod);
+            let (Some(Some(from)), Some(Some(to)), None) = (rods.next(), rods.next(), rods.next())
+            else {
+                return Err(format!("'{}' is not a rod pair like AC", pair));
+            };
+            let mv = game
+                .try_move(from, to)
+                .map_err(|err| format!("move {} is illegal: {}", i + 1, err))?;
+            run.record(time, mv);
+        }
+        Ok(run)
+    }
+}
+
+// Best finished run for one number of discs and rods
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PersonalBest {
+    pub time: f64,
+    pub splits: Vec<f64>,
+}
+
+// Personal bests, keyed like "5x3" for 5 discs on 3 rods
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Records {
+    pub bests: BTreeMap<String, PersonalBest>,
+}
+
+fn record_key(n_discs: u32, n_rods: usize) -> String {
+    format!("{}x{}", n_discs, n_rods)
+}
+
+impl Records {
+    pub fn from_toml(text: &str) -> Result<Records, String> {
+        toml::from_str(text).map_err(|err| err.to_string())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("records are always valid TOML")
+    }
+
+    pub fn best(&self, n_discs: u32, n_rods: usize) -> Option<&PersonalBest> {
+        self.bests.get(&record_key(n_discs, n_rods))
+    }
+
+    // Keeps a verified run if it beats the personal best, returning whether it did
+    pub fn submit(&mut self, run: &Run) -> bool {
+        let Ok(time) = run.verify() else {
+            return false;
+        };
+        if self
+            .best(run.n_discs, run.n_rods)
+            .is_some_and(|best| best.time <= time)
+        {
+            return false;
+        }
+
+        let splits = run
+            .splits()
+            .into_iter()
+            .map(|split| split.unwrap())
+            .collect();
+        self.bests.insert(
+            record_key(run.n_discs, run.n_rods),
+            PersonalBest { time, splits },
+        );
+        true
+    }
+}
+
+// Like 1:02.345, or 2.345 under a minute
+pub fn format_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    let (minutes, millis) = (millis / 60_000, millis % 60_000);
+    if minutes > 0 {
+        format!("{}:{:02}.{:03}", minutes, millis / 1000, millis % 1000)
+    } else {
+        format!("{}.{:03}", millis / 1000, millis % 1000)
+    }
+}
+
+// Difference to a personal best, like +0.120 or -1:02.345
+pub fn format_delta(delta: f64) -> String {
+    let sign = if delta < 0.0 { '-' } else { '+' };
+    format!("{}{}", sign, format_time(delta.abs()))
+}
@@ -0,0 +1,102 @@
+// Pixel-art disc and rod images generated in code, in grayscale so they can be tinted.
+// Sizes are in art pixels; each one is drawn `pixel_size` screen pixels wide.
+
+use image::{Rgba, RgbaImage};
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ArtStyle {
+    // Screen pixels per art pixel
+    pub pixel_size: f64,
+    // Art pixels cut off each corner diagonally
+    pub bevel: u32,
+    // Thickness of the dark border, in art pixels
+    pub outline: u32,
+    // 0 is flat, 1 is a strong light from the top left
+    pub shading: f64,
+}
+
+impl Default for ArtStyle {
+    fn default() -> ArtStyle {
+        ArtStyle {
+            pixel_size: 5.0,
+            bevel: 1,
+            outline: 1,
+            shading: 0.5,
+        }
+    }
+}
+
+const SHADE_OUTLINE: f64 = 60.0;
+const SHADE_BODY: f64 = 190.0;
+const SHADE_ROD: f64 = 160.0;
+// Added to every pixel of highlighted images
+const HIGHLIGHT_BOOST: f64 = 45.0;
+
+const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+fn gray(shade: f64) -> Rgba<u8> {
+    let value = shade.round().clamp(0.0, 255.0) as u8;
+    Rgba([value, value, value, 255])
+}
+
+impl ArtStyle {
+    // Size in art pixels of something `length` screen pixels long, at least one
+    pub fn art_pixels(&self, length: f64) -> u32 {
+        (length / self.pixel_size).round().max(1.0) as u32
+    }
+
+    // Distance of a pixel from the nearest edge, given its distances from the nearest
+    // side and from the nearest end, with bevelled corners counting as edges.
+    // None for pixels cut off by the bevel.
+    fn edge_distance(&self, dx: u32, dy: u32) -> Option<u32> {
+        let corner = dx + dy;
+        if corner < self.bevel {
+            return None;
+        }
+        Some(dx.min(dy).min(corner - self.bevel))
+    }
+
+    pub fn disc_image(&self, width: u32, height: u32, highlight: bool) -> RgbaImage {
+        let boost = if highlight { HIGHLIGHT_BOOST } else { 0.0 };
+
+        RgbaImage::from_fn(width, height, |x, y| {
+            let dx = x.min(width - 1 - x);
+            match self.edge_distance(dx, y.min(height - 1 - y)) {
+                None => TRANSPARENT,
+                Some(d) if d < self.outline => gray(SHADE_OUTLINE + boost),
+                Some(d) => {
+                    // Lit from above, with a bright line just inside the top outline
+                    let t = y as f64 / (height - 1).max(1) as f64;
+                    let mut shade = SHADE_BODY + self.shading * 60.0 * (1.0 - 2.0 * t);
+                    if y == self.outline && d == y {
+                        shade += self.shading * 30.0;
+                    }
+                    gray(shade + boost)
+                }
+            }
+        })
+    }
+
+    // Rods are only bevelled at the top, since their base is hidden by the discs
+    pub fn rod_image(&self, width: u32, height: u32, highlight: bool) -> RgbaImage {
+        let boost = if highlight {
+            HIGHLIGHT_BOOST + 20.0
+        } else {
+            0.0
+        };
+
+        RgbaImage::from_fn(width, height, |x, y| {
+            match self.edge_distance(x.min(width - 1 - x), y) {
+                None => TRANSPARENT,
+                Some(d) if d < self.outline => gray(SHADE_OUTLINE + boost),
+                Some(_) => {
+                    // Lit from the left
+                    let t = x as f64 / (width - 1).max(1) as f64;
+                    gray(SHADE_ROD + self.shading * 70.0 * (1.0 - 2.0 * t) + boost)
+                }
+            }
+        })
+    }
+}
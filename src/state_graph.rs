@@ -0,0 +1,194 @@
+use hanoi::game::{Board, State};
+use std::collections::VecDeque;
+
+// The state graph is only drawn for three rods, where it forms a Sierpinski triangle
+pub const GRAPH_RODS: usize = 3;
+pub const GRAPH_MAX_DISCS: u32 = 6;
+
+const COLOR_PANEL_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const COLOR_EDGE: [f32; 4] = [0.35, 0.35, 0.35, 1.0];
+const COLOR_TRAIL: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_OPTIMAL: [f32; 4] = [0.2, 0.9, 0.4, 1.0];
+const COLOR_CURRENT: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+
+// Corners of the triangle for each rod, in a unit square
+const CORNERS: [[f64; 2]; GRAPH_RODS] = [[0.5, 0.0], [0.0, 1.0], [1.0, 1.0]];
+
+pub struct StateGraph {
+    n_discs: u32,
+    positions: Vec<[f64; 2]>,
+    edges: Vec<(usize, usize)>,
+}
+
+pub fn state_index(state: &[usize]) -> usize {
+    state
+        .iter()
+        .rev()
+        .fold(0, |index, rod| index * GRAPH_RODS + rod)
+}
+
+fn index_state(mut index: usize, n_discs: u32) -> State {
+    let mut state: State = vec![];
+    for _ in 0..n_discs {
+        state.push(index % GRAPH_RODS);
+        index /= GRAPH_RODS;
+    }
+    state
+}
+
+fn neighbours(state: &[usize]) -> Vec<State> {
+    let board = Board::from_state(state, GRAPH_RODS);
+    board
+        .legal_moves()
+        .iter()
+        .map(|mv| {
+            let mut next = state.to_vec();
+            next[mv.disc as usize] = mv.to;
+            next
+        })
+        .collect()
+}
+
+impl StateGraph {
+    pub fn new(n_discs: u32) -> StateGraph {
+        let n_states = GRAPH_RODS.pow(n_discs);
+        let scale = (2u32.pow(n_discs) - 1) as f64;
+
+        let mut positions: Vec<[f64; 2]> = vec![];
+        let mut edges: Vec<(usize, usize)> = vec![];
+
+        for index in 0..n_states {
+            let state = index_state(index, n_discs);
+
+            // Every disc pulls the point towards the corner of its rod,
+            // bigger discs pulling twice as far as the next smaller one
+            let mut pos = [0.0, 0.0];
+            for (disc, rod) in state.iter().enumerate() {
+                let weight = 2u32.pow(disc as u32) as f64 / scale;
+                pos[0] += CORNERS[*rod][0] * weight;
+                pos[1] += CORNERS[*rod][1] * weight;
+            }
+            positions.push(pos);
+
+            for next in neighbours(&state) {
+                let next_index = state_index(&next);
+                if next_index > index {
+                    edges.push((index, next_index));
+                }
+            }
+        }
+
+        StateGraph {
+            n_discs,
+            positions,
+            edges,
+        }
+    }
+
+    pub fn n_discs(&self) -> u32 {
+        self.n_discs
+    }
+
+    // Breadth first search from one state to another, both ends included
+    pub fn shortest_path(&self, from: &[usize], to: &[usize]) -> Vec<State> {
+        let n_states = self.positions.len();
+        let start = state_index(from);
+        let goal = state_index(to);
+
+        let mut previous: Vec<Option<usize>> = vec![None; n_states];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        previous[start] = Some(start);
+        queue.push_back(start);
+
+        while let Some(index) = queue.pop_front() {
+            if index == goal {
+                break;
+            }
+            for next in neighbours(&index_state(index, self.n_discs)) {
+                let next_index = state_index(&next);
+                if previous[next_index].is_none() {
+                    previous[next_index] = Some(index);
+                    queue.push_back(next_index);
+                }
+            }
+        }
+
+        let mut path: Vec<State> = vec![];
+        let mut index = goal;
+        while let Some(prev) = previous[index] {
+            path.push(index_state(index, self.n_discs));
+            if index == start {
+                break;
+            }
+            index = prev;
+        }
+        path.reverse();
+        path
+    }
+
+    pub fn render(
+        &self,
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+        rect: [f64; 4],
+        trail: &[State],
+        optimal: &[State],
+    ) {
+        graphics::rectangle(COLOR_PANEL_BACKGROUND, rect, c.transform, gl);
+
+        let margin = 15.0;
+        let size = (rect[2] - margin * 2.0).min(rect[3] - margin * 2.0);
+        let origin = [
+            rect[0] + (rect[2] - size) / 2.0,
+            rect[1] + (rect[3] - size) / 2.0,
+        ];
+        let to_screen = |index: usize| -> [f64; 2] {
+            let pos = self.positions[index];
+            [origin[0] + pos[0] * size, origin[1] + pos[1] * size]
+        };
+
+        let draw_segment = |gl: &mut opengl_graphics::GlGraphics,
+                            color: [f32; 4],
+                            radius: f64,
+                            a: usize,
+                            b: usize| {
+            let (pa, pb) = (to_screen(a), to_screen(b));
+            graphics::line(color, radius, [pa[0], pa[1], pb[0], pb[1]], c.transform, gl);
+        };
+
+        for (a, b) in self.edges.iter() {
+            draw_segment(gl, COLOR_EDGE, 0.5, *a, *b);
+        }
+
+        for pair in optimal.windows(2) {
+            draw_segment(
+                gl,
+                COLOR_OPTIMAL,
+                1.5,
+                state_index(&pair[0]),
+                state_index(&pair[1]),
+            );
+        }
+
+        for pair in trail.windows(2) {
+            draw_segment(
+                gl,
+                COLOR_TRAIL,
+                1.5,
+                state_index(&pair[0]),
+                state_index(&pair[1]),
+            );
+        }
+
+        if let Some(current) = trail.last() {
+            let pos = to_screen(state_index(current));
+            let radius = 4.0;
+            graphics::ellipse(
+                COLOR_CURRENT,
+                [pos[0] - radius, pos[1] - radius, radius * 2.0, radius * 2.0],
+                c.transform,
+                gl,
+            );
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// Results of every challenge played, kept so progress can be looked back on
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    MoveLimit,
+    TimeLimit,
+    // One hole of a par course
+    Par,
+    // A whole par course, with the total of its holes
+    ParCourse,
+    // A level of the campaign
+    Campaign,
+}
+
+pub const MODES: [(&str, Mode); 5] = [
+    ("move limit", Mode::MoveLimit),
+    ("time limit", Mode::TimeLimit),
+    ("par hole", Mode::Par),
+    ("par course", Mode::ParCourse),
+    ("campaign", Mode::Campaign),
+];
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Entry {
+    pub mode: Mode,
+    pub discs: u32,
+    pub rods: usize,
+    pub moves: u64,
+    // Moves of the optimal solution, or par when playing against par
+    pub optimal: u64,
+    pub seconds: f64,
+    pub won: bool,
+    // Unix time the game ended at
+    pub finished_at: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Stats {
+    #[serde(default)]
+    pub entries: Vec<Entry>,
+}
+
+impl Stats {
+    pub fn from_toml(text: &str) -> Result<Stats, String> {
+        toml::from_str(text).map_err(|err| err.to_string())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("stats are always valid TOML")
+    }
+
+    pub fn add(&mut self, entry: Entry) {
+        self.entries.push(entry);
+    }
+
+    fn of(&self, mode: Mode) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(move |entry| entry.mode == mode)
+    }
+
+    pub fn played(&self, mode: Mode) -> usize {
+        self.of(mode).count()
+    }
+
+    pub fn won(&self, mode: Mode) -> usize {
+        self.of(mode).filter(|entry| entry.won).count()
+    }
+
+    // Fewest moves over optimal (or par) of any win
+    pub fn best(&self, mode: Mode) -> Option<i64> {
+        self.of(mode)
+            .filter(|entry| entry.won)
+            .map(|entry| entry.moves as i64 - entry.optimal as i64)
+            .min()
+    }
+}
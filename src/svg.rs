@@ -0,0 +1,181 @@
+// Vector drawings of boards and solutions, using the same layout and theme
+// as the game, for printed worksheets
+
+use std::fmt::Write;
+
+use crate::color::RGBColor;
+use crate::game::{rod_name, Board, Game, Move};
+use crate::layout::{disc_rect, rod_rect, BOARD_HEIGHT, BOARD_WIDTH};
+use crate::theme::Theme;
+
+// Size of each step of a solution strip, as a fraction of the board
+const STEP_SCALE: f64 = 0.25;
+const STEP_GAP: f64 = 16.0;
+const LABEL_HEIGHT: f64 = 28.0;
+const LABEL_FONT_SIZE: f64 = 18.0;
+
+const OUTLINE_SHADE: f64 = 0.35;
+
+fn hex([r, g, b]: RGBColor) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Darker version of a colour, as drawn by the pixel-art outline
+fn darken(color: RGBColor) -> RGBColor {
+    color.map(|c| (c as f64 * OUTLINE_SHADE) as u8)
+}
+
+// Black or white, whichever stands out against the colour
+fn text_color([r, g, b]: RGBColor) -> RGBColor {
+    let luma = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    if luma > 128.0 {
+        [0, 0, 0]
+    } else {
+        [255, 255, 255]
+    }
+}
+
+fn write_rect(svg: &mut String, [x, y, width, height]: [f64; 4], fill: RGBColor, theme: &Theme) {
+    let art = &theme.art;
+    let outline = art.outline as f64 * art.pixel_size;
+    let corner = art.bevel as f64 * art.pixel_size;
+
+    write!(
+        svg,
+        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{:.1}\" fill=\"{}\"",
+        x + outline / 2.0,
+        y + outline / 2.0,
+        width - outline,
+        height - outline,
+        corner,
+        hex(fill)
+    )
+    .unwrap();
+    if outline > 0.0 {
+        write!(
+            svg,
+            " stroke=\"{}\" stroke-width=\"{:.1}\"",
+            hex(darken(fill)),
+            outline
+        )
+        .unwrap();
+    }
+    svg.push_str("/>\n");
+}
+
+// The board's elements, in board coordinates
+fn write_board(svg: &mut String, board: &Board, theme: &Theme) {
+    writeln!(
+        svg,
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+        BOARD_WIDTH,
+        BOARD_HEIGHT,
+        hex(theme.play_area_background)
+    )
+    .unwrap();
+
+    let n_rods = board.n_rods();
+    let n_discs = board.n_discs();
+    for rod in 0..n_rods {
+        write_rect(svg, rod_rect(rod, n_rods), theme.rod.tint, theme);
+    }
+    for (rod, discs) in board.rods().iter().enumerate() {
+        for (level, disc) in discs.iter().enumerate() {
+            let rect = disc_rect(*disc, n_discs, rod, n_rods, level as u32);
+            write_rect(svg, rect, theme.disc_color(*disc, n_discs), theme);
+        }
+    }
+}
+
+fn svg_header(svg: &mut String, width: f64, height: f64) {
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        width, height
+    )
+    .unwrap();
+}
+
+pub fn board_svg(board: &Board, theme: &Theme) -> String {
+    let mut svg = String::new();
+    svg_header(&mut svg, BOARD_WIDTH as f64, BOARD_HEIGHT as f64);
+    write_board(&mut svg, board, theme);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Notation for a move, like `1. disc 1: A → C`
+pub fn move_label(number: usize, mv: &Move) -> String {
+    format!(
+        "{}. disc {}: {} \u{2192} {}",
+        number,
+        mv.disc + 1,
+        rod_name(mv.from),
+        rod_name(mv.to)
+    )
+}
+
+// The starting board and the board after every move, in rows of `columns` labelled steps.
+// The moves must be legal from the starting position of `n_discs` discs.
+pub fn solution_svg(
+    n_discs: u32,
+    n_rods: usize,
+    moves: &[Move],
+    columns: usize,
+    theme: &Theme,
+) -> String {
+    let cell_width = BOARD_WIDTH as f64 * STEP_SCALE;
+    let cell_height = BOARD_HEIGHT as f64 * STEP_SCALE + LABEL_HEIGHT;
+    let n_steps = moves.len() + 1;
+    let columns = columns.clamp(1, n_steps);
+    let rows = n_steps.div_ceil(columns);
+
+    let width = STEP_GAP + columns as f64 * (cell_width + STEP_GAP);
+    let height = STEP_GAP + rows as f64 * (cell_height + STEP_GAP);
+
+    let mut svg = String::new();
+    svg_header(&mut svg, width, height);
+    writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        hex(theme.background)
+    )
+    .unwrap();
+
+    let mut game = Game::new(n_discs, n_rods);
+    for step in 0..n_steps {
+        let label = if step == 0 {
+            String::from("Start")
+        } else {
+            let mv = moves[step - 1];
+            game.try_move(mv.from, mv.to)
+                .expect("illegal move in solution");
+            move_label(step, &mv)
+        };
+
+        let x = STEP_GAP + (step % columns) as f64 * (cell_width + STEP_GAP);
+        let y = STEP_GAP + (step / columns) as f64 * (cell_height + STEP_GAP);
+        writeln!(
+            svg,
+            "<g transform=\"translate({:.1} {:.1}) scale({})\">",
+            x, y, STEP_SCALE
+        )
+        .unwrap();
+        write_board(&mut svg, game.board(), theme);
+        svg.push_str("</g>\n");
+
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\" fill=\"{}\">{}</text>",
+            x + cell_width / 2.0,
+            y + cell_height - (LABEL_HEIGHT - LABEL_FONT_SIZE) / 2.0,
+            LABEL_FONT_SIZE,
+            hex(text_color(theme.background)),
+            label
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,23 @@
+// Draws the pixel font with the OpenGL backend
+
+use hanoi::font::for_each_pixel;
+pub use hanoi::font::{text_width, GLYPH_HEIGHT};
+
+pub fn draw_text(
+    text: &str,
+    x: f64,
+    y: f64,
+    scale: f64,
+    color: [f32; 4],
+    c: graphics::Context,
+    gl: &mut opengl_graphics::GlGraphics,
+) {
+    for_each_pixel(text, |px, py| {
+        graphics::rectangle(
+            color,
+            [x + px as f64 * scale, y + py as f64 * scale, scale, scale],
+            c.transform,
+            gl,
+        );
+    });
+}
@@ -1,74 +1,24 @@
 extern crate image;
 
+use crate::assets::Assets;
+use hanoi::color::{rgb_to_gl, RGBColor};
+use hanoi::sprites::ArtStyle;
+use hanoi::theme::RodStyle;
 use image::*;
-use std::path::*;
+use std::collections::HashMap;
+use std::rc::Rc;
 
-// Types
-type RGBColor = [u8; 3];
-type HSVColor = [u8; 3];
-
-fn hsv_to_rgb(hsv: HSVColor) -> RGBColor {
-    let mut h = hsv[0] as f64 / 255.0;
-    let s = hsv[1] as f64 / 255.0;
-    let v = hsv[2] as f64 / 255.0;
-
-    let mut r: f64;
-    let mut g: f64;
-    let mut b: f64;
-
-    if s > 0.0 {
-        if h == 1.0 {
-            h = 0.0;
-        }
-
-        let i = (h * 6.0) as u32;
-        let f = h * 6.0 - i as f64;
-
-        let w = v * (1.0 - s);
-        let q = v * (1.0 - s * f);
-        let t = v * (1.0 - s * (1.0 - f));
-
-        (r, g, b) = match i {
-            0 => (v, t, w),
-            1 => (q, v, w),
-            2 => (w, v, t),
-            3 => (w, q, v),
-            4 => (t, w, v),
-            5 => (v, w, q),
-            _ => (v, v, v),
-        }
-    } else {
-        (r, g, b) = (v, v, v);
-    }
-
-    r = r * 255.0;
-    g = g * 255.0;
-    b = b * 255.0;
-
-    let rgb: RGBColor = [r as u8, g as u8, b as u8];
-    rgb
-}
+// Colour that leaves a texture unchanged when drawn with it
+pub const NO_TINT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
 
 pub struct DiscTexture {
-    pub left: opengl_graphics::Texture,
-    pub middle: opengl_graphics::Texture,
-    pub right: opengl_graphics::Texture,
-    pub left_highlight: opengl_graphics::Texture,
-    pub middle_highlight: opengl_graphics::Texture,
-    pub right_highlight: opengl_graphics::Texture,
-}
-
-fn load_image(path: PathBuf) -> RgbaImage {
-    let img = image::open(path).expect("File not found");
-    img.to_rgba8()
+    pub normal: opengl_graphics::Texture,
+    pub highlight: opengl_graphics::Texture,
 }
 
-fn load_images(paths: Vec<PathBuf>) -> Vec<RgbaImage> {
-    let mut images: Vec<RgbaImage> = vec![];
-    for path in paths {
-        images.push(load_image(path));
-    }
-    images
+pub struct RodTexture {
+    pub normal: opengl_graphics::Texture,
+    pub highlight: opengl_graphics::Texture,
 }
 
 fn apply_color(mut image: RgbaImage, color: RGBColor) -> RgbaImage {
@@ -83,151 +33,102 @@ fn apply_color(mut image: RgbaImage, color: RGBColor) -> RgbaImage {
     image
 }
 
-fn apply_color_all(images: Vec<RgbaImage>, color: RGBColor) -> Vec<RgbaImage> {
-    let mut processed_images: Vec<RgbaImage> = vec![];
-
-    for image in images {
-        processed_images.push(apply_color(image, color));
-    }
-
-    processed_images
+fn create_texture(image: &RgbaImage) -> opengl_graphics::Texture {
+    opengl_graphics::Texture::from_image(
+        image,
+        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
+    )
 }
 
-fn get_file_paths(dir: &Path, files: Vec<&str>) -> Vec<PathBuf> {
-    let mut file_paths: Vec<PathBuf> = vec![];
-
-    for file in files {
-        file_paths.push(dir.join(file));
-    }
-
-    file_paths
+// Generates disc and rod textures, and loads rod images from disk once, sharing
+// the textures made from them. Disc colours are either baked into cached textures
+// on the CPU, or applied on the GPU by drawing a grayscale texture with a tint colour.
+pub struct TextureManager {
+    assets: Assets,
+    gpu_tint: bool,
+    art: ArtStyle,
+    images: HashMap<String, RgbaImage>,
+    // Keyed by width in art pixels, and colour unless tinting on the GPU
+    discs: HashMap<(u32, Option<RGBColor>), Rc<DiscTexture>>,
+    // Keyed by the normal and highlight file names
+    rods: HashMap<(Option<String>, Option<String>), Rc<RodTexture>>,
 }
 
-pub fn load_disc_texture_color(color: HSVColor) -> DiscTexture {
-    // Resolve file paths
-    let dir = Path::new("./assets");
-    let files: Vec<&str> = vec![
-        "block_grayscale_left.png",
-        "block_grayscale_middle.png",
-        "block_grayscale_right.png",
-        "block_grayscale_highlight_left.png",
-        "block_grayscale_highlight_middle.png",
-        "block_grayscale_highlight_right.png",
-    ];
-    let file_paths = get_file_paths(&dir, files);
-
-    // Load images
-    let images = load_images(file_paths);
-
-    // // Create textures for higlight
-    // let color_highlight: RGBColor = [255, 255, 100];
-
-    let color = hsv_to_rgb(color);
-    // let images_highlight = apply_color_all(images.clone(), color_highlight);
-    let images = apply_color_all(images, color);
-
-    let left = opengl_graphics::Texture::from_image(
-        &images[0],
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
-    let middle = opengl_graphics::Texture::from_image(
-        &images[1],
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
-    let right = opengl_graphics::Texture::from_image(
-        &images[2],
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
-    let left_highlight = opengl_graphics::Texture::from_image(
-        &images[3],
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
-    let middle_highlight = opengl_graphics::Texture::from_image(
-        &images[4],
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
-    let right_highlight = opengl_graphics::Texture::from_image(
-        &images[5],
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
-
-    DiscTexture {
-        left,
-        middle,
-        right,
-        left_highlight,
-        middle_highlight,
-        right_highlight,
+impl TextureManager {
+    pub fn new(assets: Assets, gpu_tint: bool, art: ArtStyle) -> TextureManager {
+        TextureManager {
+            assets,
+            gpu_tint,
+            art,
+            images: HashMap::new(),
+            discs: HashMap::new(),
+            rods: HashMap::new(),
+        }
     }
-}
 
-// pub fn load_disc_texture() -> DiscTexture {
-//     // let color: Color = [0, 0, 0];
-//     // let images = apply_color_all(images, color);
-
-//     let left = opengl_graphics::Texture::from_path(
-//         std::path::Path::new("./assets/block2_left.png"),
-//         &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-//     )
-//     .unwrap();
-//     let middle = opengl_graphics::Texture::from_path(
-//         std::path::Path::new("./assets/block2_middle.png"),
-//         &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-//     )
-//     .unwrap();
-//     let right = opengl_graphics::Texture::from_path(
-//         std::path::Path::new("./assets/block2_right.png"),
-//         &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-//     )
-//     .unwrap();
-//     let left_highlight = opengl_graphics::Texture::from_path(
-//         std::path::Path::new("./assets/block2_highlight_left.png"),
-//         &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-//     )
-//     .unwrap();
-//     let middle_highlight = opengl_graphics::Texture::from_path(
-//         std::path::Path::new("./assets/block2_highlight_middle.png"),
-//         &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-//     )
-//     .unwrap();
-//     let right_highlight = opengl_graphics::Texture::from_path(
-//         std::path::Path::new("./assets/block2_highlight_right.png"),
-//         &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-//     )
-//     .unwrap();
-
-//     DiscTexture {
-//         left,
-//         middle,
-//         right,
-//         left_highlight,
-//         middle_highlight,
-//         right_highlight,
-//     }
-// }
-
-pub fn compute_disc_color(disc: u32, n_discs: u32) -> RGBColor {
-    let hsv: HSVColor = [((255 / (n_discs)) * disc) as u8, 255, 255];
-    hsv
-}
+    pub fn art(&self) -> &ArtStyle {
+        &self.art
+    }
 
-pub struct RodTexture {
-    pub normal: opengl_graphics::Texture,
-    pub highlight: opengl_graphics::Texture,
-}
+    // Generated textures depend on the art style, so changing it drops them
+    pub fn set_art(&mut self, art: ArtStyle) {
+        self.art = art;
+        self.discs.clear();
+        self.rods.clear();
+    }
 
-pub fn load_rod_texture() -> RodTexture {
-    let image_normal = load_image(PathBuf::from("./assets/rod2.png"));
-    let image_higlight = load_image(PathBuf::from("./assets/rod2_highlight.png"));
+    // Loads a rod image, or generates one if the file is missing
+    fn load(&mut self, file: &str, size: (u32, u32), highlight: bool) -> &RgbaImage {
+        if !self.images.contains_key(file) {
+            let image = self.assets.load_image(file).unwrap_or_else(|err| {
+                eprintln!("warning: {}, using a generated texture instead", err);
+                self.art.rod_image(size.0, size.1, highlight)
+            });
+            self.images.insert(file.to_string(), image);
+        }
+        &self.images[file]
+    }
 
-    let normal = opengl_graphics::Texture::from_image(
-        &image_normal,
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
-    let highlight = opengl_graphics::Texture::from_image(
-        &image_higlight,
-        &opengl_graphics::TextureSettings::new().mag(opengl_graphics::Filter::Nearest),
-    );
+    // Texture for a disc of the given size in art pixels and colour,
+    // and the tint to draw it with
+    pub fn disc_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        color: RGBColor,
+    ) -> (Rc<DiscTexture>, [f32; 4]) {
+        let (key, tint) = if self.gpu_tint {
+            ((width, None), rgb_to_gl(color))
+        } else {
+            ((width, Some(color)), NO_TINT)
+        };
+
+        if !self.discs.contains_key(&key) {
+            let [normal, highlight] = [false, true].map(|highlight| {
+                let image = self.art.disc_image(width, height, highlight);
+                match key.1 {
+                    Some(color) => create_texture(&apply_color(image, color)),
+                    None => create_texture(&image),
+                }
+            });
+            self.discs
+                .insert(key, Rc::new(DiscTexture { normal, highlight }));
+        }
+        (self.discs[&key].clone(), tint)
+    }
 
-    RodTexture { normal, highlight }
+    // Texture for rods of the given size in art pixels, when they are generated
+    pub fn rod_texture(&mut self, style: &RodStyle, width: u32, height: u32) -> Rc<RodTexture> {
+        let key = (style.texture.clone(), style.highlight_texture.clone());
+        if !self.rods.contains_key(&key) {
+            let [normal, highlight] =
+                [(&key.0, false), (&key.1, true)].map(|(file, highlight)| match file {
+                    Some(file) => create_texture(self.load(file, (width, height), highlight)),
+                    None => create_texture(&self.art.rod_image(width, height, highlight)),
+                });
+            self.rods
+                .insert(key.clone(), Rc::new(RodTexture { normal, highlight }));
+        }
+        self.rods[&key].clone()
+    }
 }
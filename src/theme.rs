@@ -0,0 +1,170 @@
+use crate::color::{compute_disc_hsv, hsv_to_rgb, RGBColor};
+use crate::sprites::ArtStyle;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const BUILTIN_THEMES: [(&str, &str); 7] = [
+    ("rainbow", include_str!("../themes/rainbow.toml")),
+    ("pastel", include_str!("../themes/pastel.toml")),
+    ("monochrome", include_str!("../themes/monochrome.toml")),
+    ("wood", include_str!("../themes/wood.toml")),
+    // Colour-blind-safe palettes
+    ("deuteranopia", include_str!("../themes/deuteranopia.toml")),
+    ("protanopia", include_str!("../themes/protanopia.toml")),
+    ("tritanopia", include_str!("../themes/tritanopia.toml")),
+];
+
+const WHITE: RGBColor = [255, 255, 255];
+
+fn white() -> RGBColor {
+    WHITE
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Unknown(String),
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Unknown(name) => write!(
+                f,
+                "unknown theme '{}' (built-in themes are {})",
+                name,
+                BUILTIN_THEMES.map(|(name, _)| name).join(", ")
+            ),
+            ThemeError::Io(err) => write!(f, "could not read theme: {}", err),
+            ThemeError::Parse(err) => write!(f, "invalid theme: {}", err),
+        }
+    }
+}
+
+impl Error for ThemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ThemeError::Unknown(_) => None,
+            ThemeError::Io(err) => Some(err),
+            ThemeError::Parse(err) => Some(err),
+        }
+    }
+}
+
+// How disc colours are picked, from the smallest disc to the biggest
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Palette {
+    // Evenly spaced hues
+    HueSweep { saturation: u8, value: u8 },
+    // Linear blend between two colours
+    Gradient { from: RGBColor, to: RGBColor },
+    // Fixed colours, repeated if there are more discs
+    List { colors: Vec<RGBColor> },
+}
+
+// Rods use image files when given, and generated pixel art otherwise
+#[derive(Clone, Debug, Deserialize)]
+pub struct RodStyle {
+    pub texture: Option<String>,
+    pub highlight_texture: Option<String>,
+    #[serde(default = "white")]
+    pub tint: RGBColor,
+}
+
+impl Default for RodStyle {
+    fn default() -> RodStyle {
+        RodStyle {
+            texture: None,
+            highlight_texture: None,
+            tint: WHITE,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub background: RGBColor,
+    pub play_area_background: RGBColor,
+    // Multiplied into highlighted discs and rods
+    #[serde(default = "white")]
+    pub highlight_tint: RGBColor,
+    #[serde(default)]
+    pub rod: RodStyle,
+    pub palette: Palette,
+    #[serde(default)]
+    pub art: ArtStyle,
+}
+
+fn lerp(from: u8, to: u8, t: f64) -> u8 {
+    (from as f64 + (to as f64 - from as f64) * t).round() as u8
+}
+
+impl Palette {
+    pub fn disc_color(&self, disc: u32, n_discs: u32) -> RGBColor {
+        match self {
+            Palette::HueSweep { saturation, value } => {
+                hsv_to_rgb(compute_disc_hsv(disc, n_discs, *saturation, *value))
+            }
+            Palette::Gradient { from, to } => {
+                let t = if n_discs > 1 {
+                    disc as f64 / (n_discs - 1) as f64
+                } else {
+                    0.0
+                };
+                [0, 1, 2].map(|i| lerp(from[i], to[i], t))
+            }
+            Palette::List { colors } if colors.is_empty() => WHITE,
+            Palette::List { colors } => colors[disc as usize % colors.len()],
+        }
+    }
+}
+
+impl Theme {
+    pub fn from_toml(text: &str) -> Result<Theme, ThemeError> {
+        toml::from_str(text).map_err(ThemeError::Parse)
+    }
+
+    pub fn builtin(name: &str) -> Option<Theme> {
+        BUILTIN_THEMES
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, text)| Theme::from_toml(text).expect("built-in theme is invalid"))
+    }
+
+    // A built-in theme name, or the path of a theme file
+    pub fn load(name_or_path: &str) -> Result<Theme, ThemeError> {
+        if let Some(theme) = Theme::builtin(name_or_path) {
+            return Ok(theme);
+        }
+        let path = Path::new(name_or_path);
+        if !path.exists() {
+            return Err(ThemeError::Unknown(name_or_path.to_string()));
+        }
+        Theme::from_toml(&fs::read_to_string(path).map_err(ThemeError::Io)?)
+    }
+
+    // The built-in theme after this one, for cycling through them
+    pub fn next_builtin(&self) -> Theme {
+        let i = BUILTIN_THEMES
+            .iter()
+            .position(|(name, _)| *name == self.name)
+            .map_or(0, |i| (i + 1) % BUILTIN_THEMES.len());
+        Theme::builtin(BUILTIN_THEMES[i].0).unwrap()
+    }
+
+    pub fn disc_color(&self, disc: u32, n_discs: u32) -> RGBColor {
+        self.palette.disc_color(disc, n_discs)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::builtin("rainbow").unwrap()
+    }
+}
@@ -0,0 +1,209 @@
+use crate::settings::{config_path, read_config, write_config};
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use hanoi::game::Move;
+use hanoi::speedrun::{format_delta, format_time, PersonalBest, Records, Run};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// Speedrun mode: a countdown, then a timer with per-disc splits raced against
+// the personal best. Finished runs are saved as replays that can be verified.
+
+const RECORDS_FILE: &str = "records.toml";
+const REPLAYS_FOLDER: &str = "replays";
+
+// Seconds counted down before the board unlocks, and how long "GO" stays up
+const COUNTDOWN: f64 = 3.0;
+const GO_TIME: f64 = 0.75;
+
+const COLOR_PANEL_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const COLOR_TITLE: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const COLOR_TIMER: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const COLOR_PENDING: [f32; 4] = [0.45, 0.45, 0.45, 1.0];
+const COLOR_AHEAD: [f32; 4] = [0.3, 0.9, 0.4, 1.0];
+const COLOR_BEHIND: [f32; 4] = [1.0, 0.35, 0.3, 1.0];
+const COLOR_NEW_BEST: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const COLOR_COUNTDOWN: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+const TEXT_SCALE: f64 = 2.0;
+const TIMER_SCALE: f64 = 5.0;
+const COUNTDOWN_SCALE: f64 = 16.0;
+const LINE_HEIGHT: f64 = GLYPH_HEIGHT as f64 * TEXT_SCALE + 6.0;
+const TIMER_HEIGHT: f64 = GLYPH_HEIGHT as f64 * TIMER_SCALE + 12.0;
+const PANEL_PADDING: f64 = 10.0;
+
+pub struct Speedrun {
+    started: Instant,
+    run: Run,
+    best: Option<PersonalBest>,
+    // Final time, once the puzzle is solved
+    finished: Option<f64>,
+    new_best: bool,
+}
+
+// A broken records file is reported and treated as empty
+fn load_records() -> Records {
+    read_config(RECORDS_FILE, Records::from_toml)
+}
+
+impl Speedrun {
+    pub fn new(n_discs: u32, n_rods: usize) -> Speedrun {
+        Speedrun {
+            started: Instant::now(),
+            run: Run::new(n_discs, n_rods),
+            best: load_records().best(n_discs, n_rods).cloned(),
+            finished: None,
+            new_best: false,
+        }
+    }
+
+    // Seconds since the timer started, negative during the countdown
+    fn clock(&self) -> f64 {
+        self.finished
+            .unwrap_or_else(|| self.started.elapsed().as_secs_f64() - COUNTDOWN)
+    }
+
+    // The board stays locked until the countdown is over
+    pub fn counting_down(&self) -> bool {
+        self.clock() < 0.0
+    }
+
+    pub fn running(&self) -> bool {
+        self.finished.is_none()
+    }
+
+    pub fn record(&mut self, mv: Move) {
+        if self.running() {
+            self.run.record(self.clock(), mv);
+        }
+    }
+
+    // Stops the timer, saves the replay and keeps the run if it is a new best
+    pub fn finish(&mut self) {
+        let Ok(time) = self.run.verify() else {
+            return;
+        };
+        self.finished = Some(time);
+
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        if let Some(path) = config_path(REPLAYS_FOLDER) {
+            let path = path.join(format!("run-{}.toml", stamp));
+            write_config(&path, &self.run.to_toml());
+            println!("saved {}", path.display());
+        }
+
+        let mut records = load_records();
+        self.new_best = records.submit(&self.run);
+        if let (true, Some(path)) = (self.new_best, config_path(RECORDS_FILE)) {
+            write_config(&path, &records.to_toml());
+        }
+    }
+
+    pub fn render(
+        &self,
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+        x: f64,
+        y: f64,
+        width: f64,
+    ) {
+        let clock = self.clock();
+        let splits = self.run.splits();
+
+        let height = PANEL_PADDING * 2.0 + TIMER_HEIGHT + LINE_HEIGHT * (splits.len() + 1) as f64;
+        graphics::rectangle(
+            COLOR_PANEL_BACKGROUND,
+            [x, y, width, height],
+            c.transform,
+            gl,
+        );
+
+        let text_x = x + PANEL_PADDING;
+        let right = x + width - PANEL_PADDING;
+        let mut line_y = y + PANEL_PADDING;
+        let title = match (&self.best, self.new_best) {
+            (_, true) => String::from("SPEEDRUN  NEW BEST!"),
+            (Some(best), false) => format!("SPEEDRUN  PB {}", format_time(best.time)),
+            (None, false) => String::from("SPEEDRUN  NO PB YET"),
+        };
+        let title_color = if self.new_best {
+            COLOR_NEW_BEST
+        } else {
+            COLOR_TITLE
+        };
+        draw_text(&title, text_x, line_y, TEXT_SCALE, title_color, c, gl);
+
+        line_y += LINE_HEIGHT;
+        let timer = format_time(clock);
+        draw_text(
+            &timer,
+            right - text_width(&timer, TIMER_SCALE),
+            line_y,
+            TIMER_SCALE,
+            COLOR_TIMER,
+            c,
+            gl,
+        );
+
+        // The next split goes red as soon as the clock passes the best one
+        line_y += TIMER_HEIGHT;
+        let next = splits.iter().position(|split| split.is_none());
+        for (disc, split) in splits.iter().enumerate() {
+            let best = self.best.as_ref().and_then(|best| best.splits.get(disc));
+            let (time, delta) = match (split, best) {
+                (Some(time), best) => (format_time(*time), best.map(|best| time - best)),
+                (None, Some(best)) if next == Some(disc) && clock > *best => {
+                    (String::from("-"), Some(clock - best))
+                }
+                (None, _) => (String::from("-"), None),
+            };
+            let color = match delta {
+                Some(delta) if delta < 0.0 => COLOR_AHEAD,
+                Some(_) => COLOR_BEHIND,
+                None if split.is_some() => COLOR_TIMER,
+                None => COLOR_PENDING,
+            };
+
+            draw_text(
+                &format!("DISC {}", disc + 1),
+                text_x,
+                line_y,
+                TEXT_SCALE,
+                COLOR_TITLE,
+                c,
+                gl,
+            );
+            let delta = delta.map(format_delta).unwrap_or_default();
+            draw_text(
+                &format!("{:>10} {:>10}", delta, time),
+                right - text_width("0000000000 0000000000", TEXT_SCALE),
+                line_y,
+                TEXT_SCALE,
+                color,
+                c,
+                gl,
+            );
+            line_y += LINE_HEIGHT;
+        }
+    }
+
+    // Big 3, 2, 1, GO in the middle of `area`
+    pub fn render_countdown(
+        &self,
+        c: graphics::Context,
+        gl: &mut opengl_graphics::GlGraphics,
+        area: [f64; 4],
+    ) {
+        let clock = self.clock();
+        let text = if clock < 0.0 {
+            format!("{}", (-clock).ceil())
+        } else if self.running() && clock < GO_TIME {
+            String::from("GO!")
+        } else {
+            return;
+        };
+        let x = area[0] + (area[2] - text_width(&text, COUNTDOWN_SCALE)) / 2.0;
+        let y = area[1] + (area[3] - GLYPH_HEIGHT as f64 * COUNTDOWN_SCALE) / 2.0;
+        draw_text(&text, x, y, COUNTDOWN_SCALE, COLOR_COUNTDOWN, c, gl);
+    }
+}
@@ -0,0 +1,117 @@
+// One finger drags discs like the mouse does. Two fingers are a gesture:
+// a tap or a swipe to the left undoes a move, a swipe to the right redoes it.
+
+use crate::hit_test::WindowPoint;
+
+// How far two fingers must travel sideways to count as a swipe
+const SWIPE_DISTANCE: f64 = 80.0;
+
+// What a finger did, as reported by the window
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TouchPhase {
+    Start,
+    Move,
+    End,
+    Cancel,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TouchAction {
+    Press(WindowPoint),
+    Move(WindowPoint),
+    Release(WindowPoint),
+    // The drag is abandoned and the disc goes back where it was
+    Cancel,
+    Undo,
+    Redo,
+}
+
+struct Finger {
+    id: i64,
+    start: WindowPoint,
+    position: WindowPoint,
+}
+
+#[derive(Default)]
+pub struct TouchInput {
+    fingers: Vec<Finger>,
+    // Set once a second finger comes down, until every finger is lifted
+    gesture: bool,
+    // The gesture did its action already, or got a third finger and means nothing
+    decided: bool,
+}
+
+impl TouchInput {
+    // Finger `id` did `phase` at `position`
+    pub fn handle(
+        &mut self,
+        id: i64,
+        phase: TouchPhase,
+        position: WindowPoint,
+    ) -> Option<TouchAction> {
+        match phase {
+            TouchPhase::Start => {
+                self.fingers.push(Finger {
+                    id,
+                    start: position,
+                    position,
+                });
+                match self.fingers.len() {
+                    1 if !self.gesture => Some(TouchAction::Press(position)),
+                    2 if !self.gesture => {
+                        self.gesture = true;
+                        Some(TouchAction::Cancel)
+                    }
+                    _ => {
+                        self.decided = true;
+                        None
+                    }
+                }
+            }
+            TouchPhase::Move => {
+                let finger = self.fingers.iter_mut().find(|f| f.id == id)?;
+                finger.position = position;
+                (!self.gesture).then_some(TouchAction::Move(position))
+            }
+            TouchPhase::End | TouchPhase::Cancel => {
+                let index = self.fingers.iter().position(|f| f.id == id)?;
+                if !self.gesture {
+                    self.fingers.remove(index);
+                    return Some(match phase {
+                        TouchPhase::End => TouchAction::Release(position),
+                        _ => TouchAction::Cancel,
+                    });
+                }
+
+                // The gesture is decided when the first of its fingers lifts
+                let action = if !self.decided && phase == TouchPhase::End {
+                    self.fingers[index].position = position;
+                    Some(self.swipe())
+                } else {
+                    None
+                };
+                self.decided = true;
+                self.fingers.remove(index);
+                if self.fingers.is_empty() {
+                    self.gesture = false;
+                    self.decided = false;
+                }
+                action
+            }
+        }
+    }
+
+    fn swipe(&self) -> TouchAction {
+        let dx = self
+            .fingers
+            .iter()
+            .map(|f| f.position.x - f.start.x)
+            .sum::<f64>()
+            / self.fingers.len() as f64;
+        if dx > SWIPE_DISTANCE {
+            TouchAction::Redo
+        } else {
+            TouchAction::Undo
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// Star ratings of solved puzzles and the achievements they unlock
+
+use hanoi::achievements::{rating, shortest_solution, star_text, Achievement, Achievements, Solve};
+use hanoi::game::{Board, Game, Variant, VARIANTS};
+
+fn solve(discs: u32, moves: u64, undos: u32, seconds: f64) -> Solve {
+    Solve {
+        discs,
+        rods: 3,
+        variant: Variant::Classic,
+        moves,
+        optimal: Some((1 << discs) - 1),
+        undos,
+        seconds,
+    }
+}
+
+#[test]
+fn stars_go_down_with_wasted_moves() {
+    assert_eq!(rating(7, 7), 3);
+    // Up to 25% more, rounded up
+    assert_eq!(rating(9, 7), 2);
+    assert_eq!(rating(10, 7), 1);
+    assert_eq!(rating(255, 255), 3);
+    assert_eq!(rating(319, 255), 2);
+    assert_eq!(rating(320, 255), 1);
+
+    assert_eq!(star_text(3), "***");
+    assert_eq!(star_text(1), "*--");
+    assert_eq!(star_text(0), "---");
+}
+
+#[test]
+fn solves_are_rated_against_the_shortest_solution() {
+    let mut game = Game::new(2, 3);
+    for (from, to) in [(0, 1), (1, 2), (0, 1), (2, 0), (1, 2), (0, 2)] {
+        game.try_move(from, to).unwrap();
+    }
+    game.undo();
+    game.redo();
+    let solve = Solve::new(&game, 4.0);
+    assert_eq!(solve.optimal, Some(3));
+    assert_eq!(solve.moves, 6);
+    assert_eq!(solve.undos, 1);
+    assert_eq!(solve.stars(), Some(1));
+
+    // Towers too big to search still have a known solution
+    assert_eq!(shortest_solution(&Game::new(20, 4)), Some(289));
+    let cyclic = Game::from_board(Board::with_variant(&[0, 0, 0], 3, Variant::Cyclic), 2);
+    assert_eq!(shortest_solution(&cyclic), Some(21));
+}
+
+#[test]
+fn solves_unlock_achievements_once() {
+    let mut achievements = Achievements::default();
+    let unlocked = achievements.solved(&solve(8, 255, 2, 300.0), 1);
+    assert_eq!(
+        unlocked,
+        [
+            Achievement::FirstTower,
+            Achievement::Flawless,
+            Achievement::Octave
+        ]
+    );
+    assert_eq!(
+        achievements.solved(&solve(8, 255, 0, 300.0), 2),
+        [Achievement::NoUndo]
+    );
+
+    let unlocked = achievements.solved(&solve(6, 70, 1, 45.0), 3);
+    assert_eq!(unlocked, [Achievement::Quick]);
+    assert!(achievements.solved(&solve(3, 7, 0, 5.0), 4).is_empty());
+
+    // Not optimal and too slow
+    assert_eq!(solve(8, 256, 1, 60.0).earned(), [Achievement::FirstTower]);
+}
+
+#[test]
+fn trying_every_variant_unlocks_explorer() {
+    let mut achievements = Achievements::default();
+    for variant in &VARIANTS[..3] {
+        assert!(achievements.try_variant(*variant, 1).is_empty());
+    }
+    assert!(achievements.try_variant(Variant::Classic, 2).is_empty());
+    assert_eq!(
+        achievements.try_variant(Variant::Magnetic, 3),
+        [Achievement::Explorer]
+    );
+    assert!(achievements.has(Achievement::Explorer));
+
+    let loaded = Achievements::from_toml(&achievements.to_toml()).unwrap();
+    assert_eq!(loaded, achievements);
+    assert_eq!(loaded.unlocked[0].unlocked_at, 3);
+    assert_eq!(Achievements::from_toml(""), Ok(Achievements::default()));
+}
@@ -0,0 +1,86 @@
+// Bindings files: remapping actions and everything that can be wrong with one
+
+use hanoi::bindings::{Action, Binding, Bindings, PAD_A};
+use piston::input::{HatState, Key, MouseButton};
+
+#[test]
+fn remapped_actions_replace_their_defaults() {
+    let bindings = Bindings::from_toml(
+        r#"
+        undo = ["U", "pad:x"]
+        redo = "key:I"
+        hint = []
+        "#,
+    )
+    .unwrap();
+    assert_eq!(bindings.action(Binding::Key(Key::U)), Some(Action::Undo));
+    assert_eq!(bindings.action(Binding::Gamepad(2)), Some(Action::Undo));
+    assert_eq!(bindings.action(Binding::Key(Key::Z)), None);
+    assert_eq!(bindings.action(Binding::Key(Key::I)), Some(Action::Redo));
+    assert_eq!(bindings.action(Binding::Key(Key::H)), None);
+
+    // Actions left out keep theirs
+    assert_eq!(bindings.action(Binding::Key(Key::S)), Some(Action::Solve));
+    assert_eq!(
+        bindings.action(Binding::Gamepad(PAD_A)),
+        Some(Action::PickOrPlace)
+    );
+}
+
+#[test]
+fn inputs_are_parsed_by_kind() {
+    assert_eq!(Binding::parse("Space"), Some(Binding::Key(Key::Space)));
+    assert_eq!(Binding::parse("key:F5"), Some(Binding::Key(Key::F5)));
+    assert_eq!(
+        Binding::parse("mouse:Right"),
+        Some(Binding::Mouse(MouseButton::Right))
+    );
+    assert_eq!(Binding::parse("pad:a"), Some(Binding::Gamepad(PAD_A)));
+    assert_eq!(Binding::parse("pad:9"), Some(Binding::Gamepad(9)));
+    assert_eq!(
+        Binding::parse("hat:left"),
+        Some(Binding::Hat(HatState::Left))
+    );
+    assert_eq!(Binding::parse("pad:turbo"), None);
+    assert_eq!(Binding::parse("wheel:up"), None);
+}
+
+#[test]
+fn unknown_actions_are_reported() {
+    let problems = Bindings::from_toml("undo = [\"U\"]\njump = [\"J\"]")
+        .err()
+        .unwrap();
+    assert_eq!(problems, ["unknown action 'jump'"]);
+}
+
+#[test]
+fn unknown_inputs_are_reported() {
+    let problems = Bindings::from_toml("undo = [\"U\", \"Potato\", \"pad:turbo\"]")
+        .err()
+        .unwrap();
+    assert_eq!(
+        problems,
+        [
+            "unknown input 'Potato' for 'undo'",
+            "unknown input 'pad:turbo' for 'undo'"
+        ]
+    );
+    assert_eq!(
+        Bindings::from_toml("undo = 3").err().unwrap(),
+        ["'undo' must be a list of inputs"]
+    );
+}
+
+#[test]
+fn clashes_with_defaults_are_reported() {
+    // H still gives a hint, so it can't also undo
+    let problems = Bindings::from_toml("undo = [\"H\"]").err().unwrap();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("bound to both"), "{}", problems[0]);
+    assert!(problems[0].contains("'hint'") && problems[0].contains("'undo'"));
+
+    // Moving hint off H first makes room
+    let bindings = Bindings::from_toml("undo = [\"H\"]\nhint = [\"J\"]").unwrap();
+    assert_eq!(bindings.action(Binding::Key(Key::H)), Some(Action::Undo));
+    assert_eq!(bindings.action(Binding::Key(Key::J)), Some(Action::Hint));
+}
@@ -0,0 +1,159 @@
+// Rule variants, campaign files and the progress made through them
+
+use hanoi::campaign::{Campaign, Progress};
+use hanoi::game::{Board, Game, MoveError, Variant};
+
+fn variant_game(n_discs: u32, n_rods: usize, variant: Variant) -> Game {
+    let board = Board::with_variant(&vec![0; n_discs as usize], n_rods, variant);
+    Game::from_board(board, n_rods - 1)
+}
+
+#[test]
+fn cyclic_discs_only_move_one_rod_right() {
+    let mut game = variant_game(2, 3, Variant::Cyclic);
+    assert_eq!(game.try_move(0, 2), Err(MoveError::NotAllowed));
+    assert!(game.try_move(0, 1).is_ok());
+    assert!(game.try_move(1, 2).is_ok());
+    // From the last rod back round to the first
+    assert!(game.try_move(2, 0).is_ok());
+}
+
+#[test]
+fn adjacent_discs_only_move_next_door() {
+    let mut game = variant_game(2, 4, Variant::Adjacent);
+    assert_eq!(game.try_move(0, 2), Err(MoveError::NotAllowed));
+    assert!(game.try_move(0, 1).is_ok());
+    assert!(game.try_move(1, 2).is_ok());
+    assert!(game.try_move(2, 1).is_ok());
+}
+
+#[test]
+fn magnetic_discs_turn_over_and_repel() {
+    let mut game = variant_game(2, 3, Variant::Magnetic);
+    game.try_move(0, 1).unwrap();
+    assert!(game.board().flipped(0));
+    game.try_move(0, 2).unwrap();
+    // Back on top of a disc facing the other way after turning over again
+    assert_eq!(game.try_move(1, 2), Err(MoveError::SamePoles));
+
+    game.undo();
+    assert!(!game.board().flipped(1));
+    assert!(game.board().flipped(0));
+}
+
+#[test]
+fn shortest_solutions_of_variants() {
+    assert_eq!(
+        variant_game(3, 3, Variant::Classic).moves_to_solve(),
+        Some(7)
+    );
+    assert_eq!(
+        variant_game(3, 3, Variant::Cyclic).moves_to_solve(),
+        Some(21)
+    );
+    assert_eq!(
+        variant_game(3, 3, Variant::Adjacent).moves_to_solve(),
+        Some(26)
+    );
+    assert_eq!(
+        variant_game(2, 3, Variant::Magnetic).moves_to_solve(),
+        Some(4)
+    );
+
+    // Hints follow the variant's rules
+    let game = variant_game(3, 3, Variant::Cyclic);
+    let hint = game.hint().unwrap();
+    assert_eq!((hint.from, hint.to), (0, 1));
+}
+
+#[test]
+fn builtin_campaign_is_valid() {
+    let campaign = Campaign::builtin();
+    assert_eq!(campaign.levels[0].discs, 3);
+    assert_eq!(campaign.levels[0].rods, 3);
+    for variant in [Variant::Cyclic, Variant::Adjacent, Variant::Magnetic] {
+        assert!(campaign.levels.iter().any(|level| level.variant == variant));
+    }
+    for level in &campaign.levels {
+        assert!(!level.game().is_solved());
+    }
+}
+
+#[test]
+fn bad_levels_are_all_reported() {
+    let text = r#"
+        [[levels]]
+        name = "Too many"
+        discs = 40
+        rods = 3
+        move_limit = 8
+        stars = [7, 9, 12]
+
+        [[levels]]
+        name = "Lost"
+        discs = 3
+        rods = 3
+        start = "AAZ"
+        target = "D"
+        stars = [9, 7, 12]
+
+        [[levels]]
+        name = "Too good"
+        discs = 3
+        rods = 3
+        stars = [5, 9, 12]
+
+        [[levels]]
+        name = "Too good"
+        discs = 3
+        rods = 3
+        start = "CCC"
+        stars = [7, 9, 12]
+    "#;
+    let problems = Campaign::from_toml(text).err().unwrap();
+    assert_eq!(problems.len(), 8, "{:#?}", problems);
+    assert!(problems[0].starts_with("level 1 'Too many': discs"));
+    assert!(problems
+        .iter()
+        .any(|p| p.contains("start can only use rods A to C")));
+    assert!(problems.iter().any(|p| p.contains("target must be")));
+    assert!(problems.iter().any(|p| p.contains("stars must be")));
+    assert!(problems.iter().any(|p| p.contains("no room for one star")));
+    assert!(problems.iter().any(|p| p.contains("at least 7 moves")));
+    assert!(problems.iter().any(|p| p.contains("used twice")));
+
+    // A tower taller than the board can show
+    let tall = "[[levels]]\nname = \"A\"\ndiscs = 11\nrods = 3\nstars = [2047, 2100, 2200]\n";
+    assert_eq!(
+        Campaign::from_toml(tall).err().unwrap(),
+        ["level 1 'A': discs must be from 1 to 10"]
+    );
+
+    let typo = "[[levels]]\nname = \"A\"\ndiscs = 3\nrods = 3\nstar = [7, 9, 12]\n";
+    assert!(Campaign::from_toml(typo).is_err());
+    assert!(Campaign::from_toml("levels = []").is_err());
+}
+
+#[test]
+fn levels_unlock_in_order() {
+    let campaign = Campaign::builtin();
+    let mut progress = Progress::default();
+    assert_eq!(progress.unlocked(&campaign), 1);
+
+    let first = &campaign.levels[0];
+    assert_eq!(first.stars_for(7), 3);
+    assert_eq!(first.stars_for(10), 1);
+    assert_eq!(first.stars_for(13), 0);
+
+    progress.complete(first, 2);
+    progress.complete(first, 1);
+    assert_eq!(progress.stars[&first.name], 2);
+    assert_eq!(progress.unlocked(&campaign), 2);
+
+    // Solving a later level doesn't skip the one in between
+    progress.complete(&campaign.levels[2], 3);
+    assert_eq!(progress.unlocked(&campaign), 2);
+
+    let progress = Progress::from_toml(&progress.to_toml()).unwrap();
+    assert_eq!(progress.stars.len(), 2);
+}
@@ -0,0 +1,80 @@
+// Challenge budgets, par scoring and the stats they are recorded in
+
+use hanoi::challenge::{
+    format_score, move_budget, optimal_moves, outcome, par, time_budget, Budget, Outcome,
+    ParCourse, PAR_COURSE_DISCS,
+};
+use hanoi::stats::{Entry, Mode, Stats};
+
+#[test]
+fn budgets_allow_a_margin_over_the_optimal_solution() {
+    assert_eq!(optimal_moves(3, 3), 7);
+    assert_eq!(optimal_moves(4, 4), 9);
+    // 10% more, rounded up
+    assert_eq!(move_budget(3, 3), Budget::Moves(8));
+    assert_eq!(move_budget(10, 3), Budget::Moves(1023 + 103));
+    assert_eq!(time_budget(3, 3), Budget::Seconds(10.5));
+}
+
+#[test]
+fn running_over_the_budget_fails() {
+    let budget = Budget::Moves(8);
+    assert_eq!(outcome(budget, 5, 100.0, false), Outcome::Playing);
+    assert_eq!(outcome(budget, 8, 100.0, true), Outcome::Won);
+    assert_eq!(outcome(budget, 9, 0.0, false), Outcome::Failed);
+
+    let budget = Budget::Seconds(10.0);
+    assert_eq!(outcome(budget, 50, 9.9, true), Outcome::Won);
+    assert_eq!(outcome(budget, 0, 10.1, false), Outcome::Failed);
+}
+
+#[test]
+fn par_courses_are_scored_like_golf() {
+    let mut course = ParCourse::new(3);
+    assert_eq!(course.hole(), Some(PAR_COURSE_DISCS[0]));
+
+    // Par for 3 discs is 7 moves and 25%, so 9
+    assert_eq!(par(3, 3), 9);
+    course.finish_hole(7);
+    assert_eq!(course.score(), -2);
+    course.finish_hole(par(4, 3) + 3);
+    assert_eq!(course.score(), 1);
+
+    for discs in &PAR_COURSE_DISCS[2..] {
+        course.finish_hole(par(*discs, 3));
+    }
+    assert_eq!(course.hole(), None);
+    assert_eq!(course.cards.len(), PAR_COURSE_DISCS.len());
+
+    assert_eq!(format_score(0), "E");
+    assert_eq!(format_score(3), "+3");
+    assert_eq!(format_score(-2), "-2");
+}
+
+#[test]
+fn stats_count_games_by_mode() {
+    let entry = |mode, moves, won| Entry {
+        mode,
+        discs: 3,
+        rods: 3,
+        moves,
+        optimal: 7,
+        seconds: 12.5,
+        won,
+        finished_at: 1_700_000_000,
+    };
+    let mut stats = Stats::default();
+    stats.add(entry(Mode::MoveLimit, 8, true));
+    stats.add(entry(Mode::MoveLimit, 9, false));
+    stats.add(entry(Mode::MoveLimit, 7, true));
+    stats.add(entry(Mode::TimeLimit, 20, false));
+
+    assert_eq!(stats.played(Mode::MoveLimit), 3);
+    assert_eq!(stats.won(Mode::MoveLimit), 2);
+    assert_eq!(stats.best(Mode::MoveLimit), Some(0));
+    assert_eq!(stats.best(Mode::TimeLimit), None);
+    assert_eq!(stats.played(Mode::Par), 0);
+
+    assert_eq!(Stats::from_toml(&stats.to_toml()), Ok(stats));
+    assert_eq!(Stats::from_toml(""), Ok(Stats::default()));
+}
@@ -0,0 +1,103 @@
+// Animated exports: frames, delays and captions of the GIF and APNG encoders
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::process::Command;
+
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, RgbaImage};
+
+fn export(name: &str, args: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let status = Command::new(env!("CARGO_BIN_EXE_hanoi"))
+        .arg("export-gif")
+        .args(args.split_whitespace())
+        .arg("-o")
+        .arg(&path)
+        .status()
+        .unwrap();
+    assert!(status.success(), "export-gif {:?} failed", args);
+    path
+}
+
+// Delays in milliseconds and images of every frame of an APNG
+fn apng_frames(name: &str, args: &str) -> (Vec<u32>, Vec<RgbaImage>) {
+    let path = export(name, args);
+    let mut reader = png::Decoder::new(File::open(path).unwrap())
+        .read_info()
+        .unwrap();
+    let n_frames = reader.info().animation_control.unwrap().num_frames;
+    let (mut delays, mut images) = (vec![], vec![]);
+    for _ in 0..n_frames {
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        let control = reader.info().frame_control.unwrap();
+        delays.push(1000 * control.delay_num as u32 / control.delay_den as u32);
+        images.push(RgbaImage::from_raw(info.width, info.height, buffer).unwrap());
+    }
+    (delays, images)
+}
+
+#[test]
+fn gif_frames_hold_the_start_and_end() {
+    let path = export(
+        "one_disc.gif",
+        "--discs 1 --fps 10 --move-time 0.5 --width 64",
+    );
+    let frames = GifDecoder::new(BufReader::new(File::open(path).unwrap()))
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+    let delays: Vec<u32> = frames
+        .iter()
+        .map(|frame| {
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            numer / denom
+        })
+        .collect();
+    // The start, four in-between frames and the move landing
+    assert_eq!(delays, [1000, 100, 100, 100, 100, 2000]);
+    assert_eq!(frames[0].buffer().dimensions(), (64, 36));
+}
+
+#[test]
+fn long_solutions_get_a_frame_per_move() {
+    let (delays, _) = apng_frames(
+        "four_discs.png",
+        "--discs 4 --fps 100 --move-time 1 --width 64",
+    );
+    assert_eq!(delays.len(), 16);
+    assert_eq!(delays[0], 1000);
+    assert!(delays[1..15].iter().all(|delay| *delay == 1000));
+    assert_eq!(delays[15], 2000);
+}
+
+#[test]
+fn captions_are_drawn_in_the_corner() {
+    let args = "--discs 1 --fps 2 --move-time 0.5";
+    let (delays, plain) = apng_frames("plain.png", args);
+    let (_, captioned) = apng_frames("captioned.png", &format!("{} --caption", args));
+    assert_eq!(delays, [1000, 2000]);
+
+    // Only the top left, where the caption goes, is drawn over
+    for (plain, captioned) in plain.iter().zip(&captioned) {
+        let changed: Vec<(u32, u32)> = plain
+            .enumerate_pixels()
+            .filter(|(x, y, pixel)| captioned.get_pixel(*x, *y) != *pixel)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!changed.is_empty());
+        assert!(changed.iter().all(|(x, y)| *x < 320 && *y < 50));
+    }
+    // "START" is shorter than "SOLVED IN 1 MOVES"
+    let width = |image: &RgbaImage, plain: &RgbaImage| {
+        image
+            .enumerate_pixels()
+            .filter(|(x, y, pixel)| plain.get_pixel(*x, *y) != *pixel)
+            .map(|(x, _, _)| x)
+            .max()
+    };
+    assert!(width(&captioned[0], &plain[0]) < width(&captioned[1], &plain[1]));
+}
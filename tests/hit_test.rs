@@ -0,0 +1,249 @@
+// Property tests for the window/board coordinate conversions and drag and drop.
+// Each property is checked on many random boards, window sizes and pointers.
+
+use hanoi::game::{Board, MAX_RODS, MIN_RODS};
+use hanoi::hit_test::{
+    clamp_disc, disc_at, disc_near, rod_at, BoardPoint, Drag, Viewport, WindowPoint,
+};
+use hanoi::layout::{
+    disc_rect, disc_width, rod_dropbox, stacked_y, BOARD_HEIGHT, BOARD_WIDTH, DISC_HEIGHT,
+    MAX_BOARD_DISCS,
+};
+
+const CASES: usize = 2000;
+
+// Small xorshift generator so failures are reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn range(&mut self, start: usize, end: usize) -> usize {
+        start + (self.next() % (end - start) as u64) as usize
+    }
+
+    fn float(&mut self, start: f64, end: f64) -> f64 {
+        start + (self.next() >> 11) as f64 / (1u64 << 53) as f64 * (end - start)
+    }
+
+    fn board(&mut self) -> Board {
+        let n_discs = self.range(2, 9);
+        let n_rods = self.range(3, 6);
+        let state: Vec<usize> = (0..n_discs).map(|_| self.range(0, n_rods)).collect();
+        Board::from_state(&state, n_rods)
+    }
+
+    // Windows both smaller and larger than the board, and arbitrary offsets
+    fn viewport(&mut self) -> Viewport {
+        if self.next().is_multiple_of(2) {
+            Viewport::centered(self.float(200.0, 4000.0), self.float(200.0, 3000.0))
+        } else {
+            Viewport {
+                x: self.float(-2000.0, 2000.0),
+                y: self.float(-2000.0, 2000.0),
+            }
+        }
+    }
+}
+
+fn top_disc_center(board: &Board, rod: usize) -> BoardPoint {
+    let discs = &board.rods()[rod];
+    let rect = disc_rect(
+        *discs.last().unwrap(),
+        board.n_discs(),
+        rod,
+        board.n_rods(),
+        discs.len() as u32 - 1,
+    );
+    BoardPoint {
+        x: rect[0] + rect[2] / 2.0,
+        y: rect[1] + rect[3] / 2.0,
+    }
+}
+
+#[test]
+fn conversions_round_trip() {
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    for _ in 0..CASES {
+        let viewport = rng.viewport();
+        let point = WindowPoint {
+            x: rng.float(-5000.0, 5000.0),
+            y: rng.float(-5000.0, 5000.0),
+        };
+        let back = viewport.to_window(viewport.to_board(point));
+        assert!((back.x - point.x).abs() < 1e-9 && (back.y - point.y).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn top_disc_is_found_under_its_centre() {
+    let mut rng = Rng(0x9e3779b97f4a7c15);
+    for _ in 0..CASES {
+        let board = rng.board();
+        let viewport = rng.viewport();
+        for rod in 0..board.n_rods() {
+            if board.rods()[rod].is_empty() {
+                continue;
+            }
+            let pointer = viewport.to_window(top_disc_center(&board, rod));
+            assert_eq!(disc_at(&board, viewport.to_board(pointer)), Some(rod));
+        }
+    }
+}
+
+#[test]
+fn touches_near_a_lone_disc_pick_it_up() {
+    let mut rng = Rng(0x5851f42d4c957f2d);
+    for _ in 0..CASES {
+        // Every disc on one rod, so only one disc can be picked up
+        let n_rods = rng.range(3, 6);
+        let rod = rng.range(0, n_rods);
+        let board = Board::from_state(&vec![rod; rng.range(2, 9)], n_rods);
+
+        let margin = rng.float(0.0, 60.0);
+        let center = top_disc_center(&board, rod);
+        let half = disc_width(*board.rods()[rod].last().unwrap(), board.n_discs()) / 2.0;
+        let touch = BoardPoint {
+            x: center.x + rng.float(-half, half),
+            y: center.y - DISC_HEIGHT / 2.0 - rng.float(0.0, margin),
+        };
+        assert_eq!(disc_near(&board, touch, margin), Some(rod));
+
+        let far = BoardPoint {
+            x: center.x,
+            y: center.y - DISC_HEIGHT / 2.0 - margin - 1.0,
+        };
+        assert_eq!(disc_near(&board, far, margin), None);
+    }
+}
+
+#[test]
+fn drops_land_on_the_rod_under_the_pointer() {
+    let mut rng = Rng(0xd1b54a32d192ed03);
+    for _ in 0..CASES {
+        let board = rng.board();
+        let viewport = rng.viewport();
+        let from = rng.range(0, board.n_rods());
+        if board.rods()[from].is_empty() {
+            continue;
+        }
+
+        let press = viewport.to_window(top_disc_center(&board, from));
+        let drag = Drag::start(&board, viewport.to_board(press)).unwrap();
+        assert_eq!(drag.from, from);
+        assert_eq!(drag.disc, *board.rods()[from].last().unwrap());
+
+        // Release anywhere in another rod's column, at any height
+        let to = rng.range(0, board.n_rods());
+        let (start, end) = rod_dropbox(to, board.n_rods());
+        let release = viewport.to_window(BoardPoint {
+            x: rng.float(start, end),
+            y: rng.float(-500.0, BOARD_HEIGHT as f64 + 500.0),
+        });
+        assert_eq!(
+            drag.target(board.n_rods(), viewport.to_board(release)),
+            Some(to)
+        );
+    }
+}
+
+#[test]
+fn tallest_stack_can_be_picked_up_and_moved() {
+    let viewport = Viewport::centered(BOARD_WIDTH as f64, BOARD_HEIGHT as f64);
+    for n_rods in MIN_RODS..=MAX_RODS {
+        for rod in 0..n_rods {
+            let board = Board::from_state(&vec![rod; MAX_BOARD_DISCS as usize], n_rods);
+            let center = top_disc_center(&board, rod);
+            let pointer = viewport.to_window(center);
+            assert!(pointer.y >= 0.0 && pointer.y < BOARD_HEIGHT as f64);
+
+            let drag = Drag::start(&board, viewport.to_board(pointer)).unwrap();
+            assert_eq!((drag.from, drag.disc), (rod, 0));
+            let other = BoardPoint {
+                x: center.x + BOARD_WIDTH as f64 / n_rods as f64,
+                y: center.y,
+            };
+            let target = if rod + 1 < n_rods {
+                Some(rod + 1)
+            } else {
+                None
+            };
+            assert_eq!(drag.target(n_rods, other), target);
+        }
+    }
+    // One more disc would be off the top of the board
+    assert!(stacked_y(MAX_BOARD_DISCS - 1) >= 0.0);
+    assert!(stacked_y(MAX_BOARD_DISCS) < 0.0);
+}
+
+#[test]
+fn dropping_outside_the_board_misses() {
+    let mut rng = Rng(0xbf58476d1ce4e5b9);
+    for _ in 0..CASES {
+        let n_rods = rng.range(3, 6);
+        let x = if rng.next().is_multiple_of(2) {
+            rng.float(-1000.0, 0.0) - 1e-6
+        } else {
+            rng.float(BOARD_WIDTH as f64, BOARD_WIDTH as f64 + 1000.0)
+        };
+        assert_eq!(rod_at(n_rods, BoardPoint { x, y: 0.0 }), None);
+    }
+}
+
+#[test]
+fn dragged_disc_stays_on_the_board() {
+    let mut rng = Rng(0x94d049bb133111eb);
+    for _ in 0..CASES {
+        let board = rng.board();
+        let viewport = rng.viewport();
+        let from = rng.range(0, board.n_rods());
+        if board.rods()[from].is_empty() {
+            continue;
+        }
+
+        let press = top_disc_center(&board, from);
+        let drag = Drag::start(&board, press).unwrap();
+        let pointer = viewport.to_board(WindowPoint {
+            x: rng.float(-5000.0, 5000.0),
+            y: rng.float(-5000.0, 5000.0),
+        });
+        let position = drag.disc_position(board.n_discs(), pointer);
+        let half = disc_width(drag.disc, board.n_discs()) / 2.0;
+        assert!(position.x - half >= 0.0 && position.x + half <= BOARD_WIDTH as f64);
+        assert!(position.y >= 0.0 && position.y + DISC_HEIGHT <= BOARD_HEIGHT as f64);
+
+        // Without a pointer move the disc hasn't moved, unless wide discs on
+        // many rods already hang over the board's edge
+        let still = drag.disc_position(board.n_discs(), press);
+        let rect = disc_rect(
+            drag.disc,
+            board.n_discs(),
+            from,
+            board.n_rods(),
+            board.rods()[from].len() as u32 - 1,
+        );
+        if rect[0] < 0.0 || rect[0] + rect[2] > BOARD_WIDTH as f64 {
+            continue;
+        }
+        assert!((still.x - (rect[0] + rect[2] / 2.0)).abs() < 1e-9);
+        assert!((still.y - rect[1]).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn clamping_keeps_points_already_on_the_board() {
+    let mut rng = Rng(0x853c49e6748fea9b);
+    for _ in 0..CASES {
+        let width = rng.float(100.0, 350.0);
+        let point = BoardPoint {
+            x: rng.float(width / 2.0, BOARD_WIDTH as f64 - width / 2.0),
+            y: rng.float(0.0, BOARD_HEIGHT as f64 - DISC_HEIGHT),
+        };
+        assert_eq!(clamp_disc(point, width), point);
+    }
+}
@@ -0,0 +1,52 @@
+// Golden-image tests for the board layout, rendered on the CPU.
+// Run with UPDATE_GOLDEN=1 to rewrite the images after an intended change.
+
+use std::path::PathBuf;
+
+use hanoi::game::Board;
+use hanoi::snapshot::render_board;
+use hanoi::theme::Theme;
+
+fn check_golden(name: &str, state: &[usize], n_rods: usize, theme: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name));
+    let theme = Theme::builtin(theme).unwrap();
+    let image = render_board(&Board::from_state(state, n_rods), &theme);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        image.save(&path).unwrap();
+        return;
+    }
+
+    let golden = image::open(&path)
+        .unwrap_or_else(|err| panic!("could not open {}: {}", path.display(), err))
+        .to_rgba8();
+    assert_eq!(image.dimensions(), golden.dimensions(), "{}", name);
+    let different = image
+        .pixels()
+        .zip(golden.pixels())
+        .filter(|(a, b)| a != b)
+        .count();
+    assert_eq!(different, 0, "{} differs from {}", name, path.display());
+}
+
+#[test]
+fn start_position() {
+    check_golden("start_5_discs", &[0; 5], 3, "rainbow");
+}
+
+#[test]
+fn mid_game() {
+    check_golden("mid_game", &[0, 0, 1, 2, 0], 3, "rainbow");
+}
+
+#[test]
+fn four_rods() {
+    check_golden("four_rods_wood", &[3, 2, 1, 1], 4, "wood");
+}
+
+#[test]
+fn gradient_palette() {
+    check_golden("solved_monochrome", &[2; 6], 3, "monochrome");
+}
@@ -0,0 +1,80 @@
+// Streaming optimal solutions, for any number of rods and any range of moves
+
+use std::ops::Range;
+
+use hanoi::challenge::optimal_moves;
+use hanoi::game::{Game, Move};
+use hanoi::solver::TowerPlan;
+
+fn stream(n_discs: u32, n_rods: usize, range: Range<u128>) -> Vec<(u128, Move)> {
+    let mut moves = vec![];
+    TowerPlan::new(n_discs, n_rods)
+        .stream(0, n_rods - 1, range, &mut |i, mv| {
+            moves.push((i, mv));
+            Ok::<(), ()>(())
+        })
+        .unwrap();
+    moves
+}
+
+#[test]
+fn streamed_solutions_are_optimal_and_legal() {
+    for n_rods in 3..=6 {
+        for n_discs in 1..=9 {
+            let moves = stream(n_discs, n_rods, 0..u128::MAX);
+            assert_eq!(moves.len() as u64, optimal_moves(n_discs, n_rods));
+
+            let mut game = Game::new(n_discs, n_rods);
+            for (i, (index, mv)) in moves.iter().enumerate() {
+                assert_eq!(*index, i as u128);
+                let played = game.try_move(mv.from, mv.to);
+                assert_eq!(played, Ok(*mv), "{} discs, {} rods", n_discs, n_rods);
+            }
+            assert!(game.is_solved(), "{} discs, {} rods", n_discs, n_rods);
+        }
+    }
+    // Frame-Stewart counts for four rods
+    let four_rods: Vec<u64> = (1..=8).map(|n| optimal_moves(n, 4)).collect();
+    assert_eq!(four_rods, [1, 3, 5, 9, 13, 17, 25, 33]);
+}
+
+#[test]
+fn ranges_are_slices_of_the_full_solution() {
+    for n_rods in [3, 4, 5] {
+        let all = stream(8, n_rods, 0..u128::MAX);
+        let total = all.len() as u128;
+        for range in [
+            0..1,
+            0..10,
+            5..17,
+            40..total,
+            total - 1..total,
+            total..total + 5,
+        ] {
+            let slice = &all[range.start.min(total) as usize..range.end.min(total) as usize];
+            assert_eq!(stream(8, n_rods, range.clone()), slice, "{:?}", range);
+        }
+    }
+}
+
+#[test]
+fn ranges_skip_through_huge_towers() {
+    // Too many moves for a u128, and far too many to play through
+    assert_eq!(TowerPlan::new(200, 3).total(), None);
+
+    // The first moves of a tower are those of a smaller one with the same parity
+    let small = stream(10, 3, 0..u128::MAX);
+    assert_eq!(stream(200, 3, 0..1023), small);
+
+    // With three rods, move i + 1 moves the disc of its lowest set bit
+    let start = (1u128 << 120) - 3;
+    let moves = stream(200, 3, start..start + 6);
+    assert_eq!(moves.len(), 6);
+    for (i, mv) in &moves {
+        assert_eq!(mv.disc, (i + 1).trailing_zeros(), "move {}", i + 1);
+    }
+
+    let moves = stream(200, 4, 1_000_000..1_000_010);
+    let indices: Vec<u128> = moves.iter().map(|(i, _)| *i).collect();
+    assert_eq!(indices, (1_000_000..1_000_010).collect::<Vec<_>>());
+}
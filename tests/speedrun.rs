@@ -0,0 +1,92 @@
+// Splits, verification and replay files of timed runs
+
+use hanoi::game::Game;
+use hanoi::speedrun::{format_delta, format_time, Records, Run};
+
+// The optimal 3 disc solution, one move a second
+fn optimal_run() -> Run {
+    let mut game = Game::new(3, 3);
+    let mut run = Run::new(3, 3);
+    let pairs = [(0, 2), (0, 1), (2, 1), (0, 2), (1, 0), (1, 2), (0, 2)];
+    for (i, (from, to)) in pairs.into_iter().enumerate() {
+        run.record((i + 1) as f64, game.try_move(from, to).unwrap());
+    }
+    run
+}
+
+#[test]
+fn splits_are_when_each_disc_first_reaches_the_target() {
+    let run = optimal_run();
+    assert_eq!(run.splits(), vec![Some(1.0), Some(6.0), Some(4.0)]);
+
+    let mut unfinished = optimal_run();
+    unfinished.moves.truncate(2);
+    assert_eq!(unfinished.splits(), vec![Some(1.0), None, None]);
+}
+
+#[test]
+fn verify_accepts_a_solved_run() {
+    assert_eq!(optimal_run().verify(), Ok(7.0));
+}
+
+#[test]
+fn verify_rejects_bad_runs() {
+    let mut unsolved = optimal_run();
+    unsolved.moves.pop();
+    assert!(unsolved.verify().is_err());
+
+    let mut backwards = optimal_run();
+    backwards.moves[3].time = 0.5;
+    assert!(backwards.verify().is_err());
+
+    // Times that never end can't make a record
+    for time in [f64::NAN, f64::INFINITY] {
+        let mut endless = optimal_run();
+        endless.moves[6].time = time;
+        assert_eq!(
+            endless.verify(),
+            Err(String::from("move 7 has no valid time"))
+        );
+    }
+
+    let mut illegal = optimal_run();
+    illegal.moves.swap(0, 1);
+    assert!(illegal.verify().is_err());
+}
+
+#[test]
+fn replays_round_trip() {
+    let run = optimal_run();
+    assert_eq!(Run::from_toml(&run.to_toml()), Ok(run));
+
+    let illegal = "discs = 3\nrods = 3\nmoves = [\"AC\", \"AC\"]\ntimes = [1.0, 2.0]\n";
+    assert!(Run::from_toml(illegal).is_err());
+    let no_rods = "discs = 3\nrods = 0\nmoves = []\ntimes = []\n";
+    assert!(Run::from_toml(no_rods).is_err());
+}
+
+#[test]
+fn only_faster_runs_replace_the_personal_best() {
+    let mut records = Records::default();
+    assert!(records.submit(&optimal_run()));
+    assert_eq!(records.best(3, 3).unwrap().splits, vec![1.0, 6.0, 4.0]);
+
+    let mut slower = optimal_run();
+    slower.moves.last_mut().unwrap().time = 9.0;
+    assert!(!records.submit(&slower));
+
+    let mut unsolved = optimal_run();
+    unsolved.moves.pop();
+    assert!(!records.submit(&unsolved));
+
+    let records = Records::from_toml(&records.to_toml()).unwrap();
+    assert_eq!(records.best(3, 3).unwrap().time, 7.0);
+}
+
+#[test]
+fn times_are_formatted_to_the_millisecond() {
+    assert_eq!(format_time(2.3454), "2.345");
+    assert_eq!(format_time(62.0), "1:02.000");
+    assert_eq!(format_delta(-0.12), "-0.120");
+    assert_eq!(format_delta(0.0), "+0.000");
+}
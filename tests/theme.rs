@@ -0,0 +1,22 @@
+// Built-in themes: every disc of the tallest tower gets a colour of its own
+
+use hanoi::game::MAX_DISCS;
+use hanoi::theme::{Theme, BUILTIN_THEMES};
+
+#[test]
+fn builtin_palettes_never_repeat_a_colour() {
+    for (name, _) in BUILTIN_THEMES {
+        let theme = Theme::builtin(name).unwrap();
+        let colors: Vec<[u8; 3]> = (0..MAX_DISCS)
+            .map(|disc| theme.disc_color(disc, MAX_DISCS))
+            .collect();
+        for (disc, color) in colors.iter().enumerate() {
+            assert!(
+                !colors[..disc].contains(color),
+                "{} gives disc {} a colour used already",
+                name,
+                disc + 1
+            );
+        }
+    }
+}
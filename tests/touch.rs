@@ -0,0 +1,107 @@
+// Touch input: one finger drags, two fingers undo or redo
+
+use hanoi::hit_test::WindowPoint;
+use hanoi::touch::{TouchAction, TouchInput, TouchPhase};
+
+fn at(x: f64, y: f64) -> WindowPoint {
+    WindowPoint { x, y }
+}
+
+#[test]
+fn one_finger_drags() {
+    let mut touch = TouchInput::default();
+    let press = touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    assert_eq!(press, Some(TouchAction::Press(at(100.0, 200.0))));
+    let moved = touch.handle(1, TouchPhase::Move, at(150.0, 180.0));
+    assert_eq!(moved, Some(TouchAction::Move(at(150.0, 180.0))));
+    let release = touch.handle(1, TouchPhase::End, at(160.0, 180.0));
+    assert_eq!(release, Some(TouchAction::Release(at(160.0, 180.0))));
+
+    // A drag the window gives up on puts the disc back
+    touch.handle(2, TouchPhase::Start, at(100.0, 200.0));
+    let cancel = touch.handle(2, TouchPhase::Cancel, at(100.0, 200.0));
+    assert_eq!(cancel, Some(TouchAction::Cancel));
+
+    // Fingers that never started are ignored
+    assert_eq!(touch.handle(9, TouchPhase::Move, at(0.0, 0.0)), None);
+    assert_eq!(touch.handle(9, TouchPhase::End, at(0.0, 0.0)), None);
+}
+
+#[test]
+fn second_finger_cancels_the_drag() {
+    let mut touch = TouchInput::default();
+    touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    touch.handle(1, TouchPhase::Move, at(120.0, 200.0));
+    let second = touch.handle(2, TouchPhase::Start, at(300.0, 200.0));
+    assert_eq!(second, Some(TouchAction::Cancel));
+
+    // Fingers of a gesture don't move discs
+    assert_eq!(touch.handle(1, TouchPhase::Move, at(130.0, 200.0)), None);
+}
+
+#[test]
+fn two_finger_tap_or_swipe_left_undoes() {
+    let mut touch = TouchInput::default();
+    touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    touch.handle(2, TouchPhase::Start, at(200.0, 200.0));
+    assert_eq!(
+        touch.handle(1, TouchPhase::End, at(100.0, 200.0)),
+        Some(TouchAction::Undo)
+    );
+    // The second finger lifting doesn't count again
+    assert_eq!(touch.handle(2, TouchPhase::End, at(200.0, 200.0)), None);
+
+    touch.handle(1, TouchPhase::Start, at(500.0, 200.0));
+    touch.handle(2, TouchPhase::Start, at(600.0, 200.0));
+    touch.handle(1, TouchPhase::Move, at(300.0, 210.0));
+    touch.handle(2, TouchPhase::Move, at(400.0, 210.0));
+    assert_eq!(
+        touch.handle(2, TouchPhase::End, at(400.0, 210.0)),
+        Some(TouchAction::Undo)
+    );
+    touch.handle(1, TouchPhase::End, at(300.0, 210.0));
+}
+
+#[test]
+fn two_finger_swipe_right_redoes() {
+    let mut touch = TouchInput::default();
+    touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    touch.handle(2, TouchPhase::Start, at(200.0, 200.0));
+    touch.handle(1, TouchPhase::Move, at(250.0, 200.0));
+    assert_eq!(
+        touch.handle(2, TouchPhase::End, at(350.0, 200.0)),
+        Some(TouchAction::Redo)
+    );
+    touch.handle(1, TouchPhase::End, at(250.0, 200.0));
+
+    // A short swipe is still a tap
+    touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    touch.handle(2, TouchPhase::Start, at(200.0, 200.0));
+    assert_eq!(
+        touch.handle(2, TouchPhase::End, at(250.0, 200.0)),
+        Some(TouchAction::Undo)
+    );
+    touch.handle(1, TouchPhase::End, at(100.0, 200.0));
+
+    // After every finger is up, one finger drags again
+    let press = touch.handle(3, TouchPhase::Start, at(100.0, 200.0));
+    assert_eq!(press, Some(TouchAction::Press(at(100.0, 200.0))));
+}
+
+#[test]
+fn more_fingers_and_cancelled_gestures_do_nothing() {
+    let mut touch = TouchInput::default();
+    touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    touch.handle(2, TouchPhase::Start, at(200.0, 200.0));
+    assert_eq!(touch.handle(3, TouchPhase::Start, at(300.0, 200.0)), None);
+    for id in 1..=3 {
+        assert_eq!(touch.handle(id, TouchPhase::End, at(500.0, 200.0)), None);
+    }
+
+    touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    touch.handle(2, TouchPhase::Start, at(200.0, 200.0));
+    assert_eq!(touch.handle(1, TouchPhase::Cancel, at(100.0, 200.0)), None);
+    assert_eq!(touch.handle(2, TouchPhase::End, at(200.0, 200.0)), None);
+    let press = touch.handle(1, TouchPhase::Start, at(100.0, 200.0));
+    assert_eq!(press, Some(TouchAction::Press(at(100.0, 200.0))));
+}